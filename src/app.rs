@@ -1,11 +1,20 @@
-use strum::{Display, EnumIter, FromRepr};
-use serde::Deserialize;
-use std::collections::HashMap;
+use ratatui::widgets::ListState;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use strum::{Display, EnumIter, EnumString, FromRepr};
 
-#[derive(Debug, Deserialize)]
-pub struct Table {
-    columns: Vec<String>
-}
+use crate::{
+    clipboard,
+    dialect::Dialect,
+    functions::{self, Function},
+    init::{self, InitKind, InitSetting, ValidValues},
+    query::{
+        Aggregate, Assignment, Condition, Constraint, Expr, Join, JoinKind, LogicalOperator, NullsOrder, Operator, OrderItem,
+        Predicate, Query, RowLimit, SelectItem, SetValue, SortOrder, Statement, TableRef,
+    },
+    spec::{self, QuerySpec},
+    table::{Column, Table},
+};
 
 #[derive(Default, Clone, Copy, PartialEq, Eq)]
 pub enum AppState {
@@ -20,12 +29,20 @@ pub enum CurrentTab {
     #[default]
     #[strum(to_string = "INIT")]
     Init,
+    #[strum(to_string = "FROM")]
+    From,
     #[strum(to_string = "SELECT")]
     Select,
     #[strum(to_string = "WHERE")]
     Where,
+    #[strum(to_string = "GROUP BY")]
+    GroupBy,
     #[strum(to_string = "ORDER BY")]
     OrderBy,
+    #[strum(to_string = "LIMIT")]
+    Limit,
+    #[strum(to_string = "VALUES")]
+    Values,
 }
 
 impl CurrentTab {
@@ -44,218 +61,1823 @@ impl CurrentTab {
     }
 }
 
-#[derive(Default, Clone, Copy, Eq, PartialEq)]
-pub enum SelectedFlag {
-    #[default]
-    Selected,
-    NotSelected,
+/// A column of the SELECT tab.
+#[derive(Clone)]
+pub struct SelectedColumn {
+    pub column: usize,
+    pub alias: Option<String>,
+    pub distinct_on: bool, // a key of DISTINCT ON
+}
+
+/// A sort key of the ORDER BY tab.
+#[derive(Clone, Copy)]
+pub struct OrderKey {
+    pub column: usize,
+    pub order: SortOrder,
+    pub nulls: Option<NullsOrder>,
 }
 
-#[derive(Default, Clone, Copy)]
-pub enum OrderdFlag {
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum GroupFlag {
     #[default]
-    Asc,
-    Desc,
     Off,
+    Key,
+    Aggregate(Aggregate),
+}
+
+impl GroupFlag {
+    /// Get the next flag, skipping SUM and AVG unless `numeric`.
+    fn next(self, numeric: bool) -> Self {
+        let next = match self {
+            GroupFlag::Off => GroupFlag::Key,
+            GroupFlag::Key => GroupFlag::Aggregate(Aggregate::Count),
+            GroupFlag::Aggregate(Aggregate::Count) => GroupFlag::Aggregate(Aggregate::CountDistinct),
+            GroupFlag::Aggregate(Aggregate::CountDistinct) => GroupFlag::Aggregate(Aggregate::Sum),
+            GroupFlag::Aggregate(Aggregate::Sum) => GroupFlag::Aggregate(Aggregate::Avg),
+            GroupFlag::Aggregate(Aggregate::Avg) => GroupFlag::Aggregate(Aggregate::Min),
+            GroupFlag::Aggregate(Aggregate::Min) => GroupFlag::Aggregate(Aggregate::Max),
+            GroupFlag::Aggregate(Aggregate::Max) => GroupFlag::Off,
+        };
+        match next {
+            GroupFlag::Aggregate(Aggregate::Sum | Aggregate::Avg) if !numeric => next.next(numeric),
+            _ => next,
+        }
+    }
+}
+
+/// Row limiting of the LIMIT tab.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub struct Pagination {
+    pub rows: Option<u64>,
+    pub offset: u64,
+    pub rownum: bool, // limit with ROWNUM for Oracle before 12c
+    pub keyset: bool, // start after the last row seen, by the ORDER BY keys, instead of skipping rows
+}
+
+/// When the SELECT list is written as `*` rather than as the list of columns.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Display, EnumIter, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "kebab-case")]
+#[serde(rename_all = "kebab-case")]
+pub enum SelectStar {
+    /// When every table column is selected in table order, or none is
+    #[default]
+    Auto,
+    /// When every table column is selected in table order, selecting none is an error
+    WhenAll,
+    /// Never, selecting none is an error
+    Never,
+}
+
+impl SelectStar {
+    fn next(self) -> Self {
+        match self {
+            SelectStar::Auto => SelectStar::WhenAll,
+            SelectStar::WhenAll => SelectStar::Never,
+            SelectStar::Never => SelectStar::Auto,
+        }
+    }
+}
+
+/// Kind of statement generated, those changing rows work on the FROM table alone.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Display, EnumIter, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "UPPERCASE", ascii_case_insensitive)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementKind {
+    #[default]
+    Select,
+    Insert,
+    Update,
+    Delete,
+    /// Insert the row or update the one with the same primary key
+    Merge,
+}
+
+impl StatementKind {
+    fn next(self) -> Self {
+        match self {
+            StatementKind::Select => StatementKind::Insert,
+            StatementKind::Insert => StatementKind::Update,
+            StatementKind::Update => StatementKind::Delete,
+            StatementKind::Delete => StatementKind::Merge,
+            StatementKind::Merge => StatementKind::Select,
+        }
+    }
+
+    /// Comment over the SELECT previewing the rows the statement writes to.
+    fn preview_comment(self) -> &'static str {
+        match self {
+            StatementKind::Select => "",
+            StatementKind::Insert => "-- Rows clashing with the row to insert, none expected",
+            StatementKind::Update => "-- Rows to update",
+            StatementKind::Delete => "-- Rows to delete",
+            StatementKind::Merge => "-- Row to update, none if the row is new",
+        }
+    }
+}
+
+/// Rows of the LIMIT tab.
+pub const LIMIT_ROWS: [&str; 4] = ["Rows", "Offset", "Oracle", "Keyset"];
+
+/// A node of the WHERE tree, predicates refer to columns by their index in `base_columns`
+/// and keep the values as typed in the predicate builder.
+#[derive(Clone)]
+pub enum ConditionNode {
+    Predicate { column: usize, operator: Operator, input: String },
+    Group { operator: LogicalOperator, children: Vec<ConditionNode> },
+}
+
+impl Default for ConditionNode {
+    fn default() -> Self {
+        ConditionNode::Group { operator: LogicalOperator::And, children: Vec::new() }
+    }
+}
+
+impl ConditionNode {
+    /// Flatten the tree into the rows of the WHERE tab as `(depth, path)` pairs,
+    /// where the path holds the child index taken at each level.
+    pub fn rows(&self) -> Vec<(usize, Vec<usize>)> {
+        let mut rows = Vec::new();
+        self.collect_rows(0, &mut Vec::new(), &mut rows);
+        rows
+    }
+
+    fn collect_rows(&self, depth: usize, path: &mut Vec<usize>, rows: &mut Vec<(usize, Vec<usize>)>) {
+        rows.push((depth, path.clone()));
+        if let ConditionNode::Group { children, .. } = self {
+            for (i, child) in children.iter().enumerate() {
+                path.push(i);
+                child.collect_rows(depth + 1, path, rows);
+                path.pop();
+            }
+        }
+    }
+
+    pub fn get(&self, path: &[usize]) -> &ConditionNode {
+        match (self, path.split_first()) {
+            (ConditionNode::Group { children, .. }, Some((i, rest))) => children[*i].get(rest),
+            _ => self,
+        }
+    }
+
+    pub fn get_mut(&mut self, path: &[usize]) -> &mut ConditionNode {
+        match path.split_first() {
+            Some((i, rest)) => match self {
+                ConditionNode::Group { children, .. } => children[*i].get_mut(rest),
+                ConditionNode::Predicate { .. } => self,
+            },
+            None => self,
+        }
+    }
+
+    /// Drop the predicates on column `i` and renumber the columns after it.
+    fn remove_column(&mut self, i: usize) {
+        if let ConditionNode::Group { children, .. } = self {
+            children.retain(|child| !matches!(child, ConditionNode::Predicate { column, .. } if *column == i));
+            for child in children {
+                match child {
+                    ConditionNode::Predicate { column, .. } if *column > i => *column -= 1,
+                    ConditionNode::Predicate { .. } => {},
+                    ConditionNode::Group { .. } => child.remove_column(i),
+                }
+            }
+        }
+    }
+
+    /// Convert to the query AST, dropping empty groups and unwrapping groups with a single child.
+    fn to_condition(
+        &self,
+        predicate: &impl Fn(usize, Operator, &str) -> Result<Predicate, String>,
+    ) -> Result<Option<Condition>, String> {
+        match self {
+            ConditionNode::Predicate { column, operator, input } => {
+                Ok(Some(Condition::Predicate(predicate(*column, *operator, input)?)))
+            },
+            ConditionNode::Group { operator, children } => {
+                let mut conditions = Vec::new();
+                for child in children {
+                    conditions.extend(child.to_condition(predicate)?);
+                }
+                Ok(match conditions.len() {
+                    0 => None,
+                    1 => conditions.pop(),
+                    _ => Some(Condition::Group { operator: *operator, children: conditions }),
+                })
+            },
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct SpecifiedColumns {
-    pub selected_columns: Vec<SelectedFlag>, // for SELECT
-    pub ordered_columns: Vec<OrderdFlag>, // for ORDERD BY
-    pub where_constraints: Vec<Option<String>>, // for WHERE
+    pub selected_columns: Vec<SelectedColumn>, // for SELECT, in output order
+    pub ordered_columns: Vec<OrderKey>, // for ORDER BY, in sort priority
+    pub where_clause: ConditionNode, // for WHERE
+    pub grouped_columns: Vec<GroupFlag>, // for GROUP BY
+    pub having_constraints: Vec<Option<String>>, // for HAVING
+    pub aggregate_aliases: Vec<Option<String>>, // for the aggregates of the GROUP BY tab
+    pub values: Vec<Option<String>>, // for INSERT, UPDATE and MERGE, as typed, table columns only
 }
 
 impl SpecifiedColumns {
     pub fn new(len: usize) -> SpecifiedColumns {
         SpecifiedColumns {
-            selected_columns: vec![SelectedFlag::NotSelected; len],
-            ordered_columns: vec![OrderdFlag::Off; len],
-            where_constraints: vec![None; len],
+            selected_columns: Vec::new(),
+            ordered_columns: Vec::new(),
+            where_clause: ConditionNode::default(),
+            grouped_columns: vec![GroupFlag::Off; len],
+            having_constraints: vec![None; len],
+            aggregate_aliases: vec![None; len],
+            values: vec![None; len],
         }
     }
 }
 
 pub enum CurrentlyEditing {
+    InitKey,
+    InitValue,
     Constraint,
+    NewConstraint,
+    JoinCondition,
+    Having,
+    SessionName,
+    Search,
+    Alias,
+    AggregateAlias,
+    Expression,
+    NewExpression,
+    LimitValue,
+    Value,
+}
+
+/// A table of the FROM clause, every table but the first is joined to the ones before it.
+pub struct TableSource {
+    pub name: String,
+    pub alias: String,
+    pub join_kind: JoinKind,
+    pub join_condition: String,
+    pub file: Option<PathBuf>, // definition file, when the table was not read from the tables directory
 }
 
 #[derive(Default)]
 pub struct App {
     pub state: AppState,
     pub current_tab: CurrentTab,
-    pub base_columns: Vec<String>,
+    pub tables: Vec<TableSource>,
+    pub base_columns: Vec<Column>,
+    pub column_tables: Vec<usize>, // index into `tables` for each table column
+    pub expressions: Vec<String>, // SQL of the expression columns, listed after the table columns
+    pub pagination: Pagination,
+    pub statement: StatementKind,
+    pub select_star: SelectStar,
+    pub distinct: bool, // SELECT DISTINCT, or DISTINCT ON the flagged columns of the SELECT list
     pub current_column: usize,
     pub specified_columns: SpecifiedColumns,
+    pub count_all: bool, // COUNT(*), listed after the columns in the GROUP BY tab
+    pub count_all_having: Option<String>,
+    pub count_all_alias: Option<String>,
     pub constraint_input: String,
+    pub editing_column: usize, // column picked in the WHERE popup
+    pub editing_operator: Operator, // operator picked in the WHERE popup
+    pub input_error: Option<String>,
     pub currently_editing: Option<CurrentlyEditing>,
-    pub init_config: Vec<(String, String)>,
+    pub init_config: Vec<InitSetting>,
+    pub init_config_changed: bool, // whether the INIT settings need saving
+    pub dialect: Dialect,
+    pub session_name: Option<String>, // name the session was loaded from or last saved as
+    pub notice: Option<String>, // message shown in a popup until a key is pressed
+    pub show_preview: bool, // whether the SQL preview is shown next to the tabs
+    pub preview_scroll: u16, // lines of the SQL preview scrolled past
+    pub filter: String, // search filter of the current tab
+    pub list_state: ListState, // viewport of the rows of the current tab
+    pub page_height: usize, // rows the viewport showed when last drawn
+    pub quit_warned: bool, // whether quitting was refused once because the query cannot be generated
 }
 
 impl App {
-    pub fn new(table: Table) -> App {
-        let len = table.columns.len();
+    /// Create the app for the named tables, in the order they appear in the FROM clause.
+    pub fn new(tables: Vec<(String, Table)>, dialect: Dialect, init_config: Vec<InitSetting>) -> App {
+        let mut sources = Vec::<TableSource>::new();
+        let mut base_columns = Vec::<Column>::new();
+        let mut column_tables = Vec::<usize>::new();
+
+        for (i, (name, table)) in tables.into_iter().enumerate() {
+            let mut alias = table.alias.unwrap_or_else(|| name.chars().take(1).collect::<String>().to_lowercase());
+            if sources.iter().any(|source| source.alias == alias) {
+                alias = format!("{}{}", alias, i + 1);
+            }
+            let source = TableSource {
+                join_condition: infer_join_condition(&sources, &base_columns, &column_tables, &name, &alias, &table.columns),
+                name,
+                alias,
+                join_kind: JoinKind::Inner,
+                file: None,
+            };
+            sources.push(source);
+            column_tables.extend(std::iter::repeat_n(i, table.columns.len()));
+            base_columns.extend(table.columns);
+        }
+
+        let len = base_columns.len();
         App {
             state: AppState::Running,
             current_tab: CurrentTab::Init,
-            base_columns: table.columns,
+            tables: sources,
+            base_columns,
+            column_tables,
+            expressions: Vec::new(),
+            pagination: Pagination::default(),
+            statement: StatementKind::default(),
+            select_star: SelectStar::default(),
+            distinct: false,
             current_column: 0,
             specified_columns: SpecifiedColumns::new(len),
+            count_all: false,
+            count_all_having: None,
+            count_all_alias: None,
             constraint_input: String::new(),
+            editing_column: 0,
+            editing_operator: Operator::Eq,
+            input_error: None,
             currently_editing: None,
-            init_config:
-                Vec::from([
-                    ("LINESIZE", "10000"),
-                    ("PAGESIZE", "10000"),
-                    ("NUMWIDTH", "14"),
-                    ("COLSEP", "\",\""),
-                    ("NLS_DATE_FORMAT", "'YYYY/MM/DD_HH24:MI:SS'"),
-                ])
-                .into_iter()
-                .map(|(k, v)| (k.to_string(), v.to_string()))
-                .collect()
-                ,
+            init_config,
+            init_config_changed: false,
+            dialect,
+            session_name: None,
+            notice: None,
+            show_preview: true,
+            preview_scroll: 0,
+            filter: String::new(),
+            list_state: ListState::default(),
+            page_height: 1,
+            quit_warned: false,
         }
     }
 
     pub fn next_tab(&mut self) {
         self.current_tab = self.current_tab.next();
         self.current_column = 0;
+        self.filter = String::new();
+        self.list_state = ListState::default();
     }
 
     pub fn previous_tab(&mut self) {
         self.current_tab = self.current_tab.previous();
         self.current_column = 0;
+        self.filter = String::new();
+        self.list_state = ListState::default();
     }
 
+    /// Quit, unless the query cannot be generated and this was not asked already.
     pub fn quit(&mut self) {
+        if let Err(err) = self.check_query() {
+            if !self.quit_warned {
+                self.notice = Some(format!("{}\nPress q again to quit without a query", err));
+                self.quit_warned = true;
+                return;
+            }
+        }
         self.state = AppState::Quitting;
     }
 
-    /// Move to the previous column
+    /// Number of rows listed in the current tab
+    fn current_list_len(&self) -> usize {
+        match self.current_tab {
+            CurrentTab::Init => self.init_config.len(),
+            CurrentTab::From => self.tables.len(),
+            CurrentTab::Where => self.specified_columns.where_clause.rows().len(),
+            CurrentTab::GroupBy => self.base_columns.len() + 1,
+            CurrentTab::Limit => LIMIT_ROWS.len(),
+            CurrentTab::Values => self.column_tables.len(),
+            _ => self.base_columns.len(),
+        }
+    }
+
+    /// Label a row of the current tab is searched by, `None` for rows that never match on their own.
+    fn row_label(&self, i: usize) -> Option<String> {
+        match self.current_tab {
+            CurrentTab::Init => Some(self.init_config[i].key.clone()),
+            CurrentTab::From => Some(format!("{} {}", self.tables[i].name, self.tables[i].alias)),
+            CurrentTab::Where => {
+                let rows = self.specified_columns.where_clause.rows();
+                match self.specified_columns.where_clause.get(&rows[i].1) {
+                    ConditionNode::Predicate { column, .. } => Some(self.column_label(*column)),
+                    ConditionNode::Group { .. } => None,
+                }
+            },
+            CurrentTab::GroupBy => Some(self.group_label(i)),
+            CurrentTab::Limit => Some(LIMIT_ROWS[i].to_string()),
+            CurrentTab::Select | CurrentTab::OrderBy | CurrentTab::Values => Some(self.column_label(i)),
+        }
+    }
+
+    /// Rows of the current tab matching the search filter, all of them when there is none.
+    ///
+    /// In the WHERE tab the groups holding a matching predicate are kept to show the tree.
+    pub fn visible_rows(&self) -> Vec<usize> {
+        let len = self.current_list_len();
+        if self.filter.is_empty() {
+            return (0..len).collect();
+        }
+        let matching: Vec<bool> = (0..len)
+            .map(|i| self.row_label(i).is_some_and(|label| fuzzy_match(&label, &self.filter)))
+            .collect();
+        if self.current_tab != CurrentTab::Where {
+            return (0..len).filter(|&i| matching[i]).collect();
+        }
+        let rows = self.specified_columns.where_clause.rows();
+        (0..len)
+            .filter(|&i| (i..len).any(|j| matching[j] && rows[j].1.starts_with(&rows[i].1)))
+            .collect()
+    }
+
+    /// Move to the previous visible row
     pub fn previous_column(&mut self) {
-        if self.current_column > 0 {
-            self.current_column = self.current_column - 1;
-        } else {
-            self.current_column = self.base_columns.len() - 1;
+        let rows = self.visible_rows();
+        if let Some(&row) = rows.iter().rev().find(|&&row| row < self.current_column).or(rows.last()) {
+            self.current_column = row;
         }
     }
     
-    /// Move to the next column
+    /// Move to the next visible row
     pub fn next_column(&mut self) {
-        if self.current_tab == CurrentTab::Init {
-            if self.current_column < self.init_config.len() - 1 {
-                self.current_column = self.current_column + 1;
-            } else {
-                self.current_column = 0;
-            }
+        let rows = self.visible_rows();
+        if let Some(&row) = rows.iter().find(|&&row| row > self.current_column).or(rows.first()) {
+            self.current_column = row;
+        }
+    }
+
+    /// Move a page of rows up, stopping at the first visible row.
+    pub fn page_up(&mut self) {
+        let rows = self.visible_rows();
+        let position = rows.iter().position(|&row| row == self.current_column).unwrap_or(0);
+        if let Some(&row) = rows.get(position.saturating_sub(self.page_height)) {
+            self.current_column = row;
+        }
+    }
+
+    /// Move a page of rows down, stopping at the last visible row.
+    pub fn page_down(&mut self) {
+        let rows = self.visible_rows();
+        let position = rows.iter().position(|&row| row == self.current_column).unwrap_or(0);
+        if let Some(&row) = rows.get(position + self.page_height).or(rows.last()) {
+            self.current_column = row;
+        }
+    }
+
+    pub fn first_column(&mut self) {
+        if let Some(&row) = self.visible_rows().first() {
+            self.current_column = row;
+        }
+    }
+
+    pub fn last_column(&mut self) {
+        if let Some(&row) = self.visible_rows().last() {
+            self.current_column = row;
+        }
+    }
+
+    /// Open the search line, which filters the rows of the current tab as it is typed.
+    pub fn start_search(&mut self) {
+        self.state = AppState::Editing;
+        self.currently_editing = Some(CurrentlyEditing::Search);
+    }
+
+    /// Change the search filter and jump to the first row that contains it, or else to the first match.
+    pub fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+        let rows = self.visible_rows();
+        let filter = self.filter.to_lowercase();
+        let contains = rows.iter().find(|&&i| {
+            self.row_label(i).is_some_and(|label| label.to_lowercase().contains(&filter))
+        });
+        if let Some(&row) = contains.or(rows.first()) {
+            self.current_column = row;
+        }
+    }
+
+    pub fn clear_filter(&mut self) {
+        self.filter = String::new();
+    }
+
+    /// Select the visible columns of the SELECT tab, or deselect them if they all are.
+    pub fn toggle_visible_selection(&mut self) {
+        let rows = self.visible_rows();
+        if rows.iter().all(|&i| self.selected_column(i).is_some()) {
+            self.specified_columns.selected_columns.retain(|selected| !rows.contains(&selected.column));
         } else {
-            if self.current_column < self.base_columns.len() - 1 {
-                self.current_column = self.current_column + 1;
-            } else {
-                self.current_column = 0;
+            for i in rows {
+                if self.selected_column(i).is_none() {
+                    self.specified_columns.selected_columns.push(SelectedColumn { column: i, alias: None, distinct_on: false });
+                }
+            }
+        }
+    }
+
+    /// Position in the SELECT list and alias of a column, if it is selected.
+    pub fn selected_column(&self, i: usize) -> Option<(usize, &SelectedColumn)> {
+        self.specified_columns.selected_columns.iter().enumerate().find(|(_, selected)| selected.column == i)
+    }
+
+    /// Select the current column of the SELECT tab at the end of the list, or deselect it.
+    pub fn toggle_selected(&mut self) {
+        let i = self.current_column;
+        let selected_columns = &mut self.specified_columns.selected_columns;
+        match selected_columns.iter().position(|selected| selected.column == i) {
+            Some(position) => {
+                selected_columns.remove(position);
+            },
+            None => selected_columns.push(SelectedColumn { column: i, alias: None, distinct_on: false }),
+        }
+    }
+
+    /// Move the current column one place up (`earlier`) or down in the SELECT list.
+    pub fn move_selected_column(&mut self, earlier: bool) {
+        let i = self.current_column;
+        let selected_columns = &mut self.specified_columns.selected_columns;
+        let Some(position) = selected_columns.iter().position(|selected| selected.column == i) else {
+            return;
+        };
+        let other = if earlier { position.checked_sub(1) } else { Some(position + 1) };
+        if let Some(other) = other.filter(|&other| other < selected_columns.len()) {
+            selected_columns.swap(position, other);
+        }
+    }
+
+    pub fn toggle_select_star(&mut self) {
+        self.select_star = self.select_star.next();
+    }
+
+    /// Turn SELECT DISTINCT on or off, turning it off drops the keys of DISTINCT ON as well.
+    pub fn toggle_distinct(&mut self) {
+        self.distinct = !self.distinct;
+        if !self.distinct {
+            for selected in &mut self.specified_columns.selected_columns {
+                selected.distinct_on = false;
+            }
+        }
+    }
+
+    /// Make the current column of the SELECT tab a key of DISTINCT ON, if it is selected, or no longer one.
+    pub fn toggle_distinct_on(&mut self) {
+        if !self.dialect.supports_distinct_on() {
+            self.notice = Some(format!("DISTINCT ON is not supported by {}", self.dialect));
+            return;
+        }
+        let i = self.current_column;
+        if let Some(selected) = self.specified_columns.selected_columns.iter_mut().find(|selected| selected.column == i) {
+            selected.distinct_on = !selected.distinct_on;
+            self.distinct |= selected.distinct_on;
+        }
+    }
+
+    /// Keys of DISTINCT ON in SELECT list order, none unless the dialect supports it and the rows are not grouped.
+    pub fn distinct_on_columns(&self) -> Vec<usize> {
+        if !self.distinct || !self.dialect.supports_distinct_on() || self.is_grouping() {
+            return Vec::new();
+        }
+        let selected_columns = &self.specified_columns.selected_columns;
+        selected_columns.iter().filter(|selected| selected.distinct_on).map(|selected| selected.column).collect()
+    }
+
+    /// Open the popup to give the current column of the SELECT tab an alias, if it is selected.
+    pub fn start_editing_alias(&mut self) {
+        if let Some((_, selected)) = self.selected_column(self.current_column) {
+            self.constraint_input = selected.alias.clone().unwrap_or_default();
+            self.state = AppState::Editing;
+            self.currently_editing = Some(CurrentlyEditing::Alias);
+        }
+    }
+
+    /// Check an alias of column `i`, or of COUNT(*) past the columns, against the dialect
+    /// and the other aliases of the SELECT list and the GROUP BY aggregates.
+    pub fn validate_alias(&self, i: usize, alias: &str) -> Result<(), String> {
+        self.dialect.validate_alias(alias)?;
+        let same = |other: &Option<String>| other.as_ref().is_some_and(|other| other.eq_ignore_ascii_case(alias));
+        let taken = self.specified_columns.selected_columns.iter().any(|selected| selected.column != i && same(&selected.alias))
+            || (0..=self.base_columns.len()).any(|j| j != i && same(self.aggregate_alias(j)));
+        if taken {
+            return Err(format!("the alias {} is already used", alias));
+        }
+        Ok(())
+    }
+
+    /// Save the alias of the current column, an empty alias removes it.
+    pub fn save_alias(&mut self) {
+        let i = self.current_column;
+        let input = self.constraint_input.trim().to_string();
+        if !input.is_empty() {
+            if let Err(err) = self.validate_alias(i, &input) {
+                self.input_error = Some(err);
+                return;
+            }
+        }
+        let selected_columns = &mut self.specified_columns.selected_columns;
+        if let Some(selected) = selected_columns.iter_mut().find(|selected| selected.column == i) {
+            selected.alias = (!input.is_empty()).then_some(input);
+        }
+        self.constraint_input = String::new();
+        self.input_error = None;
+        self.currently_editing = None;
+    }
+
+    /// Enable or disable the current setting of the INIT tab.
+    pub fn toggle_init_setting(&mut self) {
+        if let Some(setting) = self.init_config.get_mut(self.current_column) {
+            setting.enabled = !setting.enabled;
+            self.init_config_changed = true;
+        }
+    }
+
+    /// Cycle the kind of the current setting of the INIT tab.
+    pub fn toggle_init_kind(&mut self) {
+        if let Some(setting) = self.init_config.get_mut(self.current_column) {
+            setting.kind = setting.kind.next();
+            self.init_config_changed = true;
+        }
+    }
+
+    /// Remove the current setting of the INIT tab.
+    pub fn delete_init_setting(&mut self) {
+        if self.current_column < self.init_config.len() {
+            self.init_config.remove(self.current_column);
+            self.current_column = self.current_column.min(self.init_config.len().saturating_sub(1));
+            self.init_config_changed = true;
+        }
+    }
+
+    /// Open the popup to add a setting, its key is typed or picked from the known settings.
+    pub fn start_adding_init_setting(&mut self) {
+        self.state = AppState::Editing;
+        self.currently_editing = Some(CurrentlyEditing::InitKey);
+    }
+
+    /// Open the popup to change the value of the current setting of the INIT tab.
+    pub fn start_editing_init_value(&mut self) {
+        if let Some(setting) = self.init_config.get(self.current_column) {
+            self.constraint_input = setting.value.clone();
+            self.state = AppState::Editing;
+            self.currently_editing = Some(CurrentlyEditing::InitValue);
+        }
+    }
+
+    /// Replace the input of the INIT popup with the next known key or value,
+    /// going backwards when `forward` is false.
+    pub fn cycle_init_input(&mut self, forward: bool) {
+        let candidates: Vec<&str> = match self.currently_editing {
+            Some(CurrentlyEditing::InitKey) => init::catalogue(self.dialect)
+                .iter()
+                .map(|known| known.key)
+                .filter(|key| !self.init_config.iter().any(|setting| setting.key.eq_ignore_ascii_case(key)))
+                .collect(),
+            Some(CurrentlyEditing::InitValue) => {
+                match init::find_known(self.dialect, &self.init_config[self.current_column].key) {
+                    Some(init::KnownSetting { values: ValidValues::OneOf(values), .. }) => values.to_vec(),
+                    _ => Vec::new(),
+                }
+            },
+            _ => Vec::new(),
+        };
+        if candidates.is_empty() {
+            return;
+        }
+        let position = candidates.iter().position(|candidate| candidate.eq_ignore_ascii_case(&self.constraint_input));
+        let next = match (position, forward) {
+            (Some(i), true) => (i + 1) % candidates.len(),
+            (Some(i), false) => (i + candidates.len() - 1) % candidates.len(),
+            (None, true) => 0,
+            (None, false) => candidates.len() - 1,
+        };
+        self.constraint_input = candidates[next].to_string();
+        self.input_error = None;
+    }
+
+    /// Add the setting typed in the INIT popup and go on to edit its value.
+    pub fn save_init_key(&mut self) {
+        let key = self.constraint_input.trim().to_string();
+        if key.is_empty() {
+            self.clear_constraint();
+            self.currently_editing = None;
+            return;
+        }
+        if self.init_config.iter().any(|setting| setting.key.eq_ignore_ascii_case(&key)) {
+            self.input_error = Some(format!("{} is already set", key));
+            return;
+        }
+        let setting = match init::find_known(self.dialect, &key) {
+            Some(known) => InitSetting::new(known.kind, known.key, known.default),
+            None => InitSetting::new(InitKind::Set, &key, ""),
+        };
+        self.constraint_input = setting.value.clone();
+        self.init_config.push(setting);
+        self.init_config_changed = true;
+        self.current_column = self.init_config.len() - 1;
+        self.input_error = None;
+        self.currently_editing = Some(CurrentlyEditing::InitValue);
+    }
+
+    /// Save the value of the INIT popup, the popup stays open if the setting does not accept it.
+    pub fn save_init_value(&mut self) {
+        let value = self.constraint_input.trim();
+        let setting = &mut self.init_config[self.current_column];
+        let value = match init::find_known(self.dialect, &setting.key) {
+            Some(known) => match known.validate(value) {
+                Ok(value) => value,
+                Err(err) => {
+                    self.input_error = Some(err);
+                    return;
+                },
+            },
+            None => value.to_string(),
+        };
+        setting.value = value;
+        self.init_config_changed = true;
+        self.clear_constraint();
+        self.currently_editing = None;
+    }
+
+    /// Whether the query joins several tables, in which case columns are qualified by table alias.
+    pub fn has_joins(&self) -> bool {
+        self.tables.len() > 1
+    }
+
+    /// Find a column by name, optionally qualified by the alias or name of its table.
+    /// Expression columns are found by their name alone.
+    pub fn find_column(&self, reference: &str) -> Option<usize> {
+        let (table, name) = match reference.split_once('.') {
+            Some((table, name)) => (Some(table.trim()), name.trim()),
+            None => (None, reference.trim()),
+        };
+        (0..self.base_columns.len()).find(|&i| {
+            self.base_columns[i].name.eq_ignore_ascii_case(name)
+                && match (table, self.column_tables.get(i)) {
+                    (Some(table), Some(&source)) => {
+                        let source = &self.tables[source];
+                        source.alias.eq_ignore_ascii_case(table) || source.name.eq_ignore_ascii_case(table)
+                    },
+                    (Some(_), None) => false,
+                    (None, _) => true,
+                }
+        })
+    }
+
+    /// SQL of a column if it is an expression column.
+    pub fn expression(&self, i: usize) -> Option<&str> {
+        let index = i.checked_sub(self.column_tables.len())?;
+        self.expressions.get(index).map(String::as_str)
+    }
+
+    /// Name of the column as shown in the column lists.
+    pub fn column_label(&self, i: usize) -> String {
+        match self.column_tables.get(i) {
+            Some(&source) if self.has_joins() => format!("{}.{}", self.tables[source].alias, self.base_columns[i].name),
+            _ => self.base_columns[i].name.clone(),
+        }
+    }
+
+    fn column_expr(&self, i: usize) -> Expr {
+        if let Some(sql) = self.expression(i) {
+            return Expr::Raw(sql.to_string());
+        }
+        Expr::Column {
+            table: self.has_joins().then(|| self.tables[self.column_tables[i]].alias.clone()),
+            name: self.base_columns[i].name.clone(),
+        }
+    }
+
+    /// Alias a column gets in the SELECT list, expression columns are named after themselves.
+    fn select_alias(&self, i: usize) -> Option<String> {
+        match self.selected_column(i) {
+            Some((_, SelectedColumn { alias: Some(alias), .. })) => Some(alias.clone()),
+            _ => self.expression(i).map(|_| self.base_columns[i].name.clone()),
+        }
+    }
+
+    /// Open the popup to add an expression column, written as `NAME = SQL`.
+    pub fn start_adding_expression(&mut self) {
+        self.constraint_input = format!("EXPR{} = ", self.expressions.len() + 1);
+        self.editing_column = self.current_column.min(self.base_columns.len().saturating_sub(1));
+        self.state = AppState::Editing;
+        self.currently_editing = Some(CurrentlyEditing::NewExpression);
+    }
+
+    /// Open the popup to change the current expression column of the SELECT tab.
+    pub fn start_editing_expression(&mut self) {
+        if let Some(sql) = self.expression(self.current_column) {
+            self.constraint_input = format!("{} = {}", self.base_columns[self.current_column].name, sql);
+            self.editing_column = self.current_column;
+            self.state = AppState::Editing;
+            self.currently_editing = Some(CurrentlyEditing::Expression);
+        }
+    }
+
+    /// Column the functions of the catalogue are applied to in the expression popup.
+    fn function_argument(&self) -> String {
+        let i = if self.expression(self.editing_column).is_some() { 0 } else { self.editing_column };
+        match self.base_columns.get(i) {
+            Some(_) => self.column_expr(i).render(self.dialect),
+            None => "x".to_string(),
+        }
+    }
+
+    /// Function of the catalogue the expression being edited was written with, if any.
+    pub fn current_function(&self) -> Option<(usize, &'static Function)> {
+        let (_, sql) = self.constraint_input.split_once('=')?;
+        let argument = self.function_argument();
+        functions::catalogue(self.dialect)
+            .iter()
+            .enumerate()
+            .find(|(_, function)| function.apply(&argument) == sql.trim())
+    }
+
+    /// Replace the SQL of the expression being edited by the next function of the catalogue.
+    pub fn cycle_expression_function(&mut self, forward: bool) {
+        let catalogue = functions::catalogue(self.dialect);
+        let next = match (self.current_function(), forward) {
+            (Some((i, _)), true) => (i + 1) % catalogue.len(),
+            (Some((i, _)), false) => (i + catalogue.len() - 1) % catalogue.len(),
+            (None, true) => 0,
+            (None, false) => catalogue.len() - 1,
+        };
+        let name = match self.constraint_input.split_once('=') {
+            Some((name, _)) => name.trim().to_string(),
+            None => self.constraint_input.trim().to_string(),
+        };
+        self.constraint_input = format!("{} = {}", name, catalogue[next].apply(&self.function_argument()));
+        self.input_error = None;
+    }
+
+    /// Add or change the expression column typed in the popup.
+    pub fn save_expression(&mut self) {
+        let Some((name, sql)) = self.constraint_input.split_once('=') else {
+            self.input_error = Some("write the expression as NAME = SQL".to_string());
+            return;
+        };
+        let (name, sql) = (name.trim().to_string(), sql.trim().to_string());
+        let editing = match self.currently_editing {
+            Some(CurrentlyEditing::Expression) => Some(self.editing_column),
+            _ => None,
+        };
+        if let Err(err) = self.validate_expression(editing, &name, &sql) {
+            self.input_error = Some(err);
+            return;
+        }
+        match editing {
+            Some(i) => {
+                self.base_columns[i].name = name;
+                self.expressions[i - self.column_tables.len()] = sql;
+            },
+            None => {
+                self.add_expression(name, sql);
+                self.current_column = self.base_columns.len() - 1;
+            },
+        }
+        self.constraint_input = String::new();
+        self.input_error = None;
+        self.currently_editing = None;
+    }
+
+    /// Check the name and SQL of expression column `i`, or of a new one.
+    pub fn validate_expression(&self, i: Option<usize>, name: &str, sql: &str) -> Result<(), String> {
+        self.dialect.validate_alias(name).map_err(|err| format!("name: {}", err))?;
+        if self.find_column(name).is_some_and(|other| Some(other) != i) {
+            return Err(format!("{} is already the name of a column", name));
+        }
+        if sql.is_empty() {
+            return Err("the expression is empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// Add an expression column after the others, selected.
+    pub fn add_expression(&mut self, name: String, sql: String) {
+        let i = self.base_columns.len();
+        self.base_columns.push(Column::untyped(name));
+        self.expressions.push(sql);
+        let columns = &mut self.specified_columns;
+        columns.grouped_columns.push(GroupFlag::Off);
+        columns.having_constraints.push(None);
+        columns.aggregate_aliases.push(None);
+        columns.selected_columns.push(SelectedColumn { column: i, alias: None, distinct_on: false });
+    }
+
+    /// Remove the current column of the SELECT tab if it is an expression column,
+    /// along with its use in the other tabs.
+    pub fn delete_expression(&mut self) {
+        let i = self.current_column;
+        if self.expression(i).is_none() {
+            return;
+        }
+        self.base_columns.remove(i);
+        self.expressions.remove(i - self.column_tables.len());
+        let columns = &mut self.specified_columns;
+        columns.grouped_columns.remove(i);
+        columns.having_constraints.remove(i);
+        columns.aggregate_aliases.remove(i);
+        columns.selected_columns.retain(|selected| selected.column != i);
+        for selected in &mut columns.selected_columns {
+            if selected.column > i {
+                selected.column -= 1;
+            }
+        }
+        columns.ordered_columns.retain(|key| key.column != i);
+        for key in &mut columns.ordered_columns {
+            if key.column > i {
+                key.column -= 1;
             }
         }
+        columns.where_clause.remove_column(i);
+        self.current_column = self.current_column.min(self.base_columns.len().saturating_sub(1));
+        if self.editing_column == i {
+            self.editing_column = 0;
+        } else if self.editing_column > i {
+            self.editing_column -= 1;
+        }
+    }
+
+    /// Cycle the join type of the current table of the FROM tab.
+    pub fn toggle_join_kind(&mut self) {
+        if self.current_column == 0 {
+            return;
+        }
+        let table = &mut self.tables[self.current_column];
+        table.join_kind = match table.join_kind {
+            JoinKind::Inner => JoinKind::Left,
+            JoinKind::Left => JoinKind::Inner,
+        };
+    }
+
+    pub fn save_join_condition(&mut self) {
+        self.tables[self.current_column].join_condition = self.constraint_input.trim().to_string();
+        self.constraint_input = String::new();
+        self.currently_editing = None;
     }
     
-    pub fn save_constraint(&mut self) {
-        let input = self.constraint_input.clone();
-        if input.len() > 0 {
-            self.specified_columns.where_constraints[self.current_column] =  Some(input.clone());
+    /// Path in the WHERE tree of the current row of the WHERE tab.
+    fn current_where_path(&self) -> Vec<usize> {
+        let mut rows = self.specified_columns.where_clause.rows();
+        if self.current_column < rows.len() {
+            rows.swap_remove(self.current_column).1
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Move the cursor of the WHERE tab to the node at `path`.
+    fn select_where_path(&mut self, path: &[usize]) {
+        let rows = self.specified_columns.where_clause.rows();
+        self.current_column = rows.iter().position(|(_, row_path)| row_path == path).unwrap_or(0);
+    }
+
+    /// Insert `node` as the last child of the current group, or after the current predicate.
+    fn insert_where_node(&mut self, node: ConditionNode) {
+        let mut path = self.current_where_path();
+        let where_clause = &mut self.specified_columns.where_clause;
+        if let ConditionNode::Group { children, .. } = where_clause.get_mut(&path) {
+            path.push(children.len());
+            children.push(node);
+        } else {
+            let index = path.pop().unwrap_or(0) + 1;
+            if let ConditionNode::Group { children, .. } = where_clause.get_mut(&path) {
+                children.insert(index, node);
+            }
+            path.push(index);
+        }
+        self.select_where_path(&path);
+    }
+
+    /// Open the WHERE popup on the current predicate, or to add a new one next to the current row.
+    pub fn start_editing_constraint(&mut self, new: bool) {
+        let path = self.current_where_path();
+        let node = self.specified_columns.where_clause.get(&path);
+        if let ConditionNode::Predicate { column, operator, input } = node {
+            self.editing_column = *column;
+            self.editing_operator = if new { Operator::Eq } else { *operator };
+            if !new {
+                self.constraint_input = input.clone();
+            }
+        } else if new {
+            // keep the column picked last, unless it has been deleted since
+            if self.editing_column >= self.base_columns.len() {
+                self.editing_column = 0;
+            }
+            self.editing_operator = Operator::Eq;
+        } else {
+            return;
+        }
+        self.state = AppState::Editing;
+        self.currently_editing = Some(if new { CurrentlyEditing::NewConstraint } else { CurrentlyEditing::Constraint });
+    }
+
+    /// Pick the previous column in the WHERE popup
+    pub fn previous_editing_column(&mut self) {
+        if self.editing_column > 0 {
+            self.editing_column -= 1;
+        } else {
+            self.editing_column = self.base_columns.len().saturating_sub(1);
+        }
+    }
+
+    /// Pick the next column in the WHERE popup
+    pub fn next_editing_column(&mut self) {
+        if self.editing_column + 1 < self.base_columns.len() {
+            self.editing_column += 1;
         } else {
-            self.specified_columns.where_constraints[self.current_column] =  None;
+            self.editing_column = 0;
+        }
+    }
+
+    /// Switch the WHERE popup to the previous operator, wrapping around to the last one.
+    pub fn previous_editing_operator(&mut self) {
+        self.editing_operator = self.editing_operator.previous();
+        self.input_error = None;
+    }
+
+    /// Switch the WHERE popup to the next operator, wrapping around to the first one.
+    pub fn next_editing_operator(&mut self) {
+        self.editing_operator = self.editing_operator.next();
+        self.input_error = None;
+    }
+
+    /// Save the predicate of the WHERE popup, the popup stays open if the values do not fit the column.
+    pub fn save_constraint(&mut self) {
+        let input = self.constraint_input.trim().to_string();
+        let operator = self.editing_operator;
+        let empty = input.is_empty() && operator.arity() != Some(0);
+        if !empty {
+            let data_type = self.base_columns[self.editing_column].data_type;
+            if let Err(err) = Constraint::parse(operator, &input, data_type) {
+                self.input_error = Some(err);
+                return;
+            }
+        }
+        let node = ConditionNode::Predicate { column: self.editing_column, operator, input };
+        match self.currently_editing {
+            Some(CurrentlyEditing::NewConstraint) if !empty => self.insert_where_node(node),
+            Some(CurrentlyEditing::Constraint) if !empty => {
+                let path = self.current_where_path();
+                *self.specified_columns.where_clause.get_mut(&path) = node;
+            },
+            Some(CurrentlyEditing::Constraint) => self.delete_where_node(),
+            _ => {},
         }
         self.constraint_input = String::new();
+        self.input_error = None;
         self.currently_editing = None;
     }
+
+    /// Predicate of the WHERE tree on column `i`, failing on values that no longer parse.
+    pub fn where_predicate(&self, i: usize, operator: Operator, input: &str) -> Result<Predicate, String> {
+        let data_type = self.base_columns[i].data_type;
+        let constraint = Constraint::parse(operator, input, data_type)
+            .map_err(|err| format!("WHERE condition on {}: {}", self.column_label(i), err))?;
+        Ok(Predicate { expr: self.column_expr(i), constraint })
+    }
+
+    /// The WHERE tree as a condition, `None` when it has no predicate.
+    fn where_condition(&self) -> Result<Option<Condition>, String> {
+        self.specified_columns
+            .where_clause
+            .to_condition(&|i, operator, input| self.where_predicate(i, operator, input))
+    }
+
+    /// Add an empty OR group next to the current row of the WHERE tab.
+    pub fn add_where_group(&mut self) {
+        self.insert_where_node(ConditionNode::Group { operator: LogicalOperator::Or, children: Vec::new() });
+    }
+
+    /// Switch the current group of the WHERE tab between AND and OR.
+    pub fn toggle_where_operator(&mut self) {
+        let path = self.current_where_path();
+        if let ConditionNode::Group { operator, .. } = self.specified_columns.where_clause.get_mut(&path) {
+            *operator = match operator {
+                LogicalOperator::And => LogicalOperator::Or,
+                LogicalOperator::Or => LogicalOperator::And,
+            };
+        }
+    }
+
+    /// Remove the current row of the WHERE tab, the root group can only be emptied.
+    pub fn delete_where_node(&mut self) {
+        let mut path = self.current_where_path();
+        let Some(index) = path.pop() else {
+            self.specified_columns.where_clause = ConditionNode::default();
+            return;
+        };
+        if let ConditionNode::Group { children, .. } = self.specified_columns.where_clause.get_mut(&path) {
+            children.remove(index);
+        }
+        self.current_column = self.current_column.min(self.current_list_len() - 1);
+    }
     
+    /// Sort priority and key of a column of the ORDER BY tab, if it is sorted on.
+    pub fn order_key(&self, i: usize) -> Option<(usize, &OrderKey)> {
+        self.specified_columns.ordered_columns.iter().enumerate().find(|(_, key)| key.column == i)
+    }
+
+    /// Cycle the current column of the ORDER BY tab through ascending, descending and unsorted,
+    /// a column that starts being sorted on comes last in priority.
+    pub fn toggle_order(&mut self) {
+        let i = self.current_column;
+        let ordered_columns = &mut self.specified_columns.ordered_columns;
+        match ordered_columns.iter().position(|key| key.column == i) {
+            None => ordered_columns.push(OrderKey { column: i, order: SortOrder::Asc, nulls: None }),
+            Some(priority) => match ordered_columns[priority].order {
+                SortOrder::Asc => ordered_columns[priority].order = SortOrder::Desc,
+                SortOrder::Desc => {
+                    ordered_columns.remove(priority);
+                },
+            },
+        }
+    }
+
+    /// Cycle where the NULLs of the current sort key go: the dialect default, first, then last.
+    pub fn toggle_nulls_order(&mut self) {
+        let i = self.current_column;
+        if let Some(key) = self.specified_columns.ordered_columns.iter_mut().find(|key| key.column == i) {
+            key.nulls = match key.nulls {
+                None => Some(NullsOrder::First),
+                Some(NullsOrder::First) => Some(NullsOrder::Last),
+                Some(NullsOrder::Last) => None,
+            };
+        }
+    }
+
+    /// Move the current sort key one place up (`earlier`) or down in priority.
+    pub fn move_order_key(&mut self, earlier: bool) {
+        let i = self.current_column;
+        let ordered_columns = &mut self.specified_columns.ordered_columns;
+        let Some(priority) = ordered_columns.iter().position(|key| key.column == i) else {
+            return;
+        };
+        let other = if earlier { priority.checked_sub(1) } else { Some(priority + 1) };
+        if let Some(other) = other.filter(|&other| other < ordered_columns.len()) {
+            ordered_columns.swap(priority, other);
+        }
+    }
+
+    /// Switch the ROWNUM style or the keyset mode of the LIMIT tab.
+    pub fn toggle_limit_setting(&mut self) {
+        match self.current_column {
+            2 => self.pagination.rownum = !self.pagination.rownum,
+            3 => self.pagination.keyset = !self.pagination.keyset,
+            _ => {},
+        }
+    }
+
+    /// Open the popup to change the number of rows or the offset of the LIMIT tab.
+    pub fn start_editing_limit(&mut self) {
+        self.constraint_input = match self.current_column {
+            0 => self.pagination.rows.map(|rows| rows.to_string()).unwrap_or_default(),
+            1 => self.pagination.offset.to_string(),
+            _ => return,
+        };
+        self.state = AppState::Editing;
+        self.currently_editing = Some(CurrentlyEditing::LimitValue);
+    }
+
+    /// Save the number typed in the LIMIT popup, leaving it empty removes the limit or the offset.
+    pub fn save_limit_value(&mut self) {
+        let input = self.constraint_input.trim();
+        let value = match input.parse::<u64>() {
+            Ok(value) => Some(value),
+            Err(_) if input.is_empty() => None,
+            Err(_) => {
+                self.input_error = Some(format!("`{}` is not a number of rows", input));
+                return;
+            },
+        };
+        match self.current_column {
+            0 => self.pagination.rows = value.filter(|rows| *rows > 0),
+            _ => self.pagination.offset = value.unwrap_or(0),
+        }
+        self.clear_constraint();
+        self.currently_editing = None;
+    }
+
+    /// Name of the parameter holding the value of column `i` in the last row of the previous page.
+    pub fn keyset_parameter(&self, i: usize) -> String {
+        let name: String = self.base_columns[i]
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+            .collect();
+        format!("last_{}", name)
+    }
+
+    /// Keys the query sorts on in priority order: those of DISTINCT ON, which must lead, then the ORDER BY tab.
+    fn sort_keys(&self) -> Vec<OrderKey> {
+        let distinct_on = self.distinct_on_columns();
+        let mut keys: Vec<OrderKey> = distinct_on
+            .iter()
+            .map(|&i| match self.order_key(i) {
+                Some((_, key)) => *key,
+                None => OrderKey { column: i, order: SortOrder::Asc, nulls: None },
+            })
+            .collect();
+        let ordered_columns = &self.specified_columns.ordered_columns;
+        keys.extend(ordered_columns.iter().filter(|key| !distinct_on.contains(&key.column)));
+        keys
+    }
+
+    /// Keys a page starts after: the sort keys, or only the DISTINCT ON keys since DISTINCT ON
+    /// returns a single row for them, which the next page must not return again.
+    pub fn keyset_keys(&self) -> Vec<OrderKey> {
+        let mut keys = self.sort_keys();
+        let distinct_on = self.distinct_on_columns();
+        if !distinct_on.is_empty() {
+            keys.truncate(distinct_on.len());
+        }
+        keys
+    }
+
+    /// Condition selecting the rows after the last one of the previous page, in ORDER BY order:
+    /// `k1 > :last_k1 OR (k1 = :last_k1 AND k2 > :last_k2) ...`
+    ///
+    /// Comparisons with NULL are never true, so every key must be NOT NULL for no row to be skipped.
+    fn keyset_condition(&self) -> Result<Condition, String> {
+        let keys = self.keyset_keys();
+        if keys.is_empty() {
+            return Err("keyset pagination needs columns to sort on in ORDER BY".to_string());
+        }
+        let plain_distinct = self.distinct && self.distinct_on_columns().is_empty();
+        if let Some(key) = keys.iter().find(|key| plain_distinct && !self.in_output(key.column)) {
+            return Err(format!(
+                "keyset pagination cannot sort on {}, which DISTINCT leaves out: select it or sort on another column",
+                self.column_label(key.column)
+            ));
+        }
+        if let Some(key) = keys.iter().find(|key| {
+            let column = &self.base_columns[key.column];
+            column.nullable && !column.primary_key
+        }) {
+            return Err(format!(
+                "keyset pagination cannot sort on {}, which may be NULL: declare it `nullable = false` or sort on another column",
+                self.column_label(key.column)
+            ));
+        }
+        let predicate = |i: usize, operator: &str| {
+            let placeholder = self.dialect.placeholder(&self.keyset_parameter(i));
+            Condition::Predicate(Predicate {
+                expr: self.column_expr(i),
+                constraint: Constraint::Raw(format!("{} {}", operator, placeholder)),
+            })
+        };
+        let mut alternatives: Vec<Condition> = (0..keys.len())
+            .map(|k| {
+                let mut children: Vec<Condition> = keys[..k].iter().map(|key| predicate(key.column, "=")).collect();
+                let operator = match keys[k].order {
+                    SortOrder::Asc => ">",
+                    SortOrder::Desc => "<",
+                };
+                children.push(predicate(keys[k].column, operator));
+                match children.len() {
+                    1 => children.remove(0),
+                    _ => Condition::Group { operator: LogicalOperator::And, children },
+                }
+            })
+            .collect();
+        Ok(match alternatives.len() {
+            1 => alternatives.remove(0),
+            _ => Condition::Group { operator: LogicalOperator::Or, children: alternatives },
+        })
+    }
+
+    /// Whether the GROUP BY tab aggregates the rows, which then decides the SELECT list.
+    pub fn is_grouping(&self) -> bool {
+        self.count_all || self.specified_columns.grouped_columns.iter().any(|flag| *flag != GroupFlag::Off)
+    }
+
+    /// Cycle the grouping of the current row of the GROUP BY tab.
+    pub fn toggle_group_flag(&mut self) {
+        let i = self.current_column;
+        if i == self.base_columns.len() {
+            self.count_all = !self.count_all;
+            return;
+        }
+        let numeric = self.base_columns[i].data_type.is_none_or(|data_type| data_type.is_numeric());
+        let columns = &mut self.specified_columns;
+        columns.grouped_columns[i] = columns.grouped_columns[i].next(numeric);
+        // Only aggregates are renamed in the GROUP BY tab, grouping keys keep the alias of the SELECT tab
+        if !matches!(columns.grouped_columns[i], GroupFlag::Aggregate(_)) {
+            columns.aggregate_aliases[i] = None;
+        }
+    }
+
+    /// Expression the current row of the GROUP BY tab stands for, if it is grouped or aggregated.
+    pub fn group_expr(&self, i: usize) -> Option<Expr> {
+        if i == self.base_columns.len() {
+            return self.count_all.then_some(Expr::CountAll);
+        }
+        match self.specified_columns.grouped_columns[i] {
+            GroupFlag::Off => None,
+            GroupFlag::Key => Some(self.column_expr(i)),
+            GroupFlag::Aggregate(function) => Some(Expr::Aggregate {
+                function,
+                arg: Box::new(self.column_expr(i)),
+            }),
+        }
+    }
+
+    /// Name of the row of the GROUP BY tab, with its aggregate function applied.
+    pub fn group_label(&self, i: usize) -> String {
+        if i == self.base_columns.len() {
+            return "COUNT(*)".to_string();
+        }
+        match self.specified_columns.grouped_columns[i] {
+            GroupFlag::Aggregate(Aggregate::CountDistinct) => format!("COUNT(DISTINCT {})", self.column_label(i)),
+            GroupFlag::Aggregate(function) => format!("{}({})", function, self.column_label(i)),
+            _ => self.column_label(i),
+        }
+    }
+
+    /// HAVING condition of the given row of the GROUP BY tab.
+    pub fn having_constraint(&self, i: usize) -> &Option<String> {
+        if i == self.base_columns.len() {
+            &self.count_all_having
+        } else {
+            &self.specified_columns.having_constraints[i]
+        }
+    }
+
+    /// Alias of the given row of the GROUP BY tab, set on aggregates only.
+    pub fn aggregate_alias(&self, i: usize) -> &Option<String> {
+        if i == self.base_columns.len() {
+            &self.count_all_alias
+        } else {
+            &self.specified_columns.aggregate_aliases[i]
+        }
+    }
+
+    /// Open the popup to give the current row of the GROUP BY tab an alias, if it is an aggregate.
+    pub fn start_editing_aggregate_alias(&mut self) {
+        if matches!(self.group_expr(self.current_column), Some(Expr::Aggregate { .. } | Expr::CountAll)) {
+            self.constraint_input = self.aggregate_alias(self.current_column).clone().unwrap_or_default();
+            self.state = AppState::Editing;
+            self.currently_editing = Some(CurrentlyEditing::AggregateAlias);
+        }
+    }
+
+    /// Save the alias of the current aggregate, an empty alias removes it.
+    pub fn save_aggregate_alias(&mut self) {
+        let i = self.current_column;
+        let input = self.constraint_input.trim().to_string();
+        if !input.is_empty() {
+            if let Err(err) = self.validate_alias(i, &input) {
+                self.input_error = Some(err);
+                return;
+            }
+        }
+        let alias = (!input.is_empty()).then_some(input);
+        if i == self.base_columns.len() {
+            self.count_all_alias = alias;
+        } else {
+            self.specified_columns.aggregate_aliases[i] = alias;
+        }
+        self.constraint_input = String::new();
+        self.input_error = None;
+        self.currently_editing = None;
+    }
+
+    /// Alias of a grouped or aggregated row in the SELECT list: the alias of an aggregate, or else the one
+    /// of the SELECT tab, which names expression columns grouped on after themselves.
+    fn group_select_alias(&self, i: usize) -> Option<String> {
+        match self.group_expr(i)? {
+            Expr::Aggregate { .. } | Expr::CountAll => self.aggregate_alias(i).clone().or_else(|| {
+                self.selected_column(i).and_then(|(_, selected)| selected.alias.clone())
+            }),
+            _ => self.select_alias(i),
+        }
+    }
+
+    pub fn save_having(&mut self) {
+        let input = self.constraint_input.trim();
+        let having = (!input.is_empty()).then(|| input.to_string());
+        if self.current_column == self.base_columns.len() {
+            self.count_all_having = having;
+        } else {
+            self.specified_columns.having_constraints[self.current_column] = having;
+        }
+        self.constraint_input = String::new();
+        self.currently_editing = None;
+    }
+
+    /// Copy the script to the clipboard, telling how it went in a notice.
+    pub fn copy_query(&mut self) {
+        self.notice = Some(match self.generate_query() {
+            Ok(script) => match clipboard::copy(&script) {
+                Ok(()) => format!("Copied {} lines to the clipboard", script.lines().count()),
+                Err(err) => format!("Could not copy to the clipboard: {}", err),
+            },
+            Err(err) => err,
+        });
+    }
+
+    pub fn toggle_preview(&mut self) {
+        self.show_preview = !self.show_preview;
+    }
+
+    /// Scroll the SQL preview by a line, the preview keeps it within the script when drawn.
+    pub fn scroll_preview(&mut self, down: bool) {
+        self.preview_scroll = match down {
+            true => self.preview_scroll.saturating_add(1),
+            false => self.preview_scroll.saturating_sub(1),
+        };
+    }
+
+    /// Open the popup to save the session, with the name it was last saved as.
+    pub fn start_saving_session(&mut self) {
+        self.constraint_input = self.session_name.clone().unwrap_or_default();
+        self.state = AppState::Editing;
+        self.currently_editing = Some(CurrentlyEditing::SessionName);
+    }
+
+    pub fn save_session(&mut self) {
+        let name = self.constraint_input.trim().to_string();
+        if name.is_empty() {
+            self.input_error = Some("enter a name for the session".to_string());
+            return;
+        }
+        let saved = spec::session_path(&name)
+            .map_err(Into::into)
+            .and_then(|path| QuerySpec::from_app(self).save(&path).map(|_| path));
+        match saved {
+            Ok(path) => {
+                self.notice = Some(format!("Session saved to {}", path.display()));
+                self.session_name = Some(name);
+                self.constraint_input = String::new();
+                self.input_error = None;
+                self.currently_editing = None;
+            },
+            Err(err) => self.input_error = Some(err.to_string()),
+        }
+    }
+
     pub fn clear_constraint(&mut self) {
         self.constraint_input = String::new();
+        self.input_error = None;
     }
     
-    pub fn generate_query(self, table_name: &String) {
-        let len = self.base_columns.len();
-        let conf_len = self.init_config.len();
-        
-        for i in 0..conf_len {
-            let (key, value) = &self.init_config[i];
-            if i == 4 {
-                println!("ALTER SESSION SET {} = {}", key, value);
-            } else {
-                println!("SET {} {}", key, value);
+    /// Build the query AST for the current selections.
+    pub fn build_query(&self) -> Result<Query, String> {
+        let columns = &self.specified_columns;
+        let table_ref = |table: &TableSource| TableRef {
+            name: table.name.clone(),
+            alias: self.has_joins().then(|| table.alias.clone()),
+        };
+        let mut query = Query {
+            from: table_ref(&self.tables[0]),
+            joins: self.tables[1..]
+                .iter()
+                .map(|table| Join {
+                    kind: table.join_kind,
+                    table: table_ref(table),
+                    condition: table.join_condition.clone(),
+                })
+                .collect(),
+            ..Query::default()
+        };
+
+        let grouping = self.is_grouping();
+        if grouping {
+            for i in 0..=self.base_columns.len() {
+                if let Some(expr) = self.group_expr(i) {
+                    if i < self.base_columns.len() && columns.grouped_columns[i] == GroupFlag::Key {
+                        query.group_by.push(expr.clone());
+                    }
+                    if let Some(constraint) = self.having_constraint(i) {
+                        query.having.push(Predicate {
+                            expr,
+                            constraint: Constraint::Raw(constraint.clone()),
+                        });
+                    }
+                }
             }
-        }
-        
-        print!("\nSELECT");
-        
-        let mut num_of_selected_columns = 0;
-        
-        for i in 0..len {
-            if self.specified_columns.selected_columns[i] == SelectedFlag::Selected {
-                num_of_selected_columns += 1;
+            // Only grouping keys and aggregates may be selected from grouped rows: the selected ones
+            // in SELECT list order, then the others in GROUP BY tab order
+            let selected = columns.selected_columns.iter().map(|selected| selected.column);
+            let others = (0..=self.base_columns.len()).filter(|&i| self.selected_column(i).is_none());
+            for i in selected.chain(others) {
+                if let Some(expr) = self.group_expr(i) {
+                    query.select.push(SelectItem { expr, alias: self.group_select_alias(i) });
+                }
             }
-        }
-        
-        if num_of_selected_columns == len || num_of_selected_columns == 0 {
-            print!(" *")
         } else {
-            let mut first_element = true;
-            for i in 0..len {
-                match self.specified_columns.selected_columns[i] {
-                    SelectedFlag::Selected =>  {
-                        if first_element {
-                            print!("\n\t{}", self.base_columns[i]);
-                            first_element = false;
-                        } else {
-                            println!(",");
-                            print!("\t{}", self.base_columns[i]);
-                        }
-                    }
-                    SelectedFlag::NotSelected => {},
+            // Selecting every table column in table order is the same as selecting none: both render as `SELECT *`
+            let everything = self.select_star != SelectStar::Never
+                && columns.selected_columns.len() == self.column_tables.len()
+                && columns
+                    .selected_columns
+                    .iter()
+                    .enumerate()
+                    .all(|(position, selected)| selected.column == position && self.select_alias(position).is_none());
+            if !everything {
+                for selected in &columns.selected_columns {
+                    query.select.push(SelectItem {
+                        expr: self.column_expr(selected.column),
+                        alias: self.select_alias(selected.column),
+                    });
                 }
             }
+            // Grouped rows are distinct already
+            query.distinct = self.distinct;
+            query.distinct_on = self.distinct_on_columns().into_iter().map(|i| self.column_expr(i)).collect();
         }
-        
-        print!("\nFROM {}", table_name);
-        
-        let mut first_element = true;
-        for i in 0..len {
-            if let Some(constraint) = &self.specified_columns.where_constraints[i] {
-                if first_element {
-                    println!("\nWHERE");
-                    print!("\t{} {}", self.base_columns[i], constraint);
-                    first_element = false;
-                } else {
-                    println!(" AND");
-                    print!("\t{} {}", self.base_columns[i], constraint);
+
+        query.where_clause = self.where_condition()?;
+
+        // Keyset pagination filters on the sort keys, which grouped queries sort on after filtering
+        let keyset = if self.pagination.keyset && !grouping { Some(self.keyset_condition()?) } else { None };
+        query.where_clause = match (query.where_clause, keyset) {
+            (Some(Condition::Group { operator: LogicalOperator::And, mut children }), Some(keyset)) => {
+                children.push(keyset);
+                Some(Condition::Group { operator: LogicalOperator::And, children })
+            },
+            (Some(condition), Some(keyset)) => {
+                Some(Condition::Group { operator: LogicalOperator::And, children: vec![condition, keyset] })
+            },
+            (condition, keyset) => condition.or(keyset),
+        };
+
+        // Plain DISTINCT can only sort on what it outputs
+        let plain_distinct = query.distinct && query.distinct_on.is_empty();
+        for key in self.sort_keys() {
+            // The key emulating NULLS FIRST/LAST would not be in the output either
+            let nulls = key.nulls.filter(|_| !plain_distinct || self.dialect.supports_nulls_order());
+            let expr = if grouping {
+                // Columns that are neither grouped nor aggregated cannot be sorted on
+                match self.group_expr(key.column) {
+                    Some(expr) => expr,
+                    None => continue,
+                }
+            } else if plain_distinct && !self.in_output(key.column) {
+                continue;
+            } else if query.distinct_on.len() > query.order_by.len() {
+                // The leading keys must be the very expressions of DISTINCT ON
+                self.column_expr(key.column)
+            } else {
+                // Sort on the alias of a renamed column, unless the dialect would need it inside an expression
+                let in_expression = nulls.is_some() && !self.dialect.supports_nulls_order();
+                match self.selected_column(key.column).and_then(|_| self.select_alias(key.column)) {
+                    Some(alias) if !in_expression || self.dialect.alias_in_order_expressions() => Expr::Alias(alias),
+                    _ => self.column_expr(key.column),
+                }
+            };
+            query.order_by.push(OrderItem { expr, order: key.order, nulls });
+        }
+
+        if self.pagination.rows.is_none() && self.pagination.offset > 0 && !self.pagination.keyset {
+            return Err(format!("an offset of {} rows needs a number of rows to fetch in LIMIT", self.pagination.offset));
+        }
+        query.limit = self.pagination.rows.map(|count| RowLimit {
+            count,
+            offset: if self.pagination.keyset { 0 } else { self.pagination.offset },
+            rownum: self.pagination.rownum,
+        });
+
+        Ok(query)
+    }
+
+    /// Whether column `i` is in the output of an ungrouped SELECT, no selection standing for `*`.
+    fn in_output(&self, i: usize) -> bool {
+        match self.specified_columns.selected_columns.is_empty() {
+            true => i < self.column_tables.len(),
+            false => self.selected_column(i).is_some(),
+        }
+    }
+
+    /// Selections the SELECT cannot render and leaves out, to tell the user about.
+    pub fn query_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.statement != StatementKind::Select {
+            return warnings;
+        }
+        if self.is_grouping() {
+            // A grouped query selects its GROUP BY keys and aggregates only
+            for selected in &self.specified_columns.selected_columns {
+                let label = self.column_label(selected.column);
+                if self.group_expr(selected.column).is_none() {
+                    warnings.push(format!("SELECT {} is left out, it is neither grouped nor aggregated", label));
+                }
+            }
+            if self.distinct {
+                warnings.push("DISTINCT is left out, grouped rows are distinct already".to_string());
+            }
+            if self.pagination.keyset {
+                warnings.push("keyset pagination is left out, grouped rows are sorted after the WHERE filters them".to_string());
+            }
+            for key in &self.specified_columns.ordered_columns {
+                if self.group_expr(key.column).is_none() {
+                    warnings.push(format!("ORDER BY {} is left out, it is neither grouped nor aggregated", self.column_label(key.column)));
+                }
+            }
+        } else if self.distinct && self.distinct_on_columns().is_empty() {
+            for key in &self.specified_columns.ordered_columns {
+                let label = self.column_label(key.column);
+                if !self.in_output(key.column) {
+                    warnings.push(format!("ORDER BY {} is left out, DISTINCT can only sort on the columns selected", label));
+                } else if key.nulls.is_some() && !self.dialect.supports_nulls_order() {
+                    warnings.push(format!(
+                        "the NULLs order of {} is left out, {} would sort on an expression that DISTINCT does not select",
+                        label, self.dialect
+                    ));
                 }
             }
         }
-        
-        let mut first_element = true;
-        for i in 0..len {
-            match self.specified_columns.ordered_columns[i] {
-                OrderdFlag::Asc =>  {
-                    if first_element {
-                        println!("\nORDER BY");
-                        print!("\t{} ASC", self.base_columns[i]);
-                        first_element = false;
-                    } else {
-                        print!(",\n\t{} ASC", self.base_columns[i]);
-                    }
+        warnings
+    }
+
+    pub fn cycle_statement(&mut self) {
+        self.statement = self.statement.next();
+    }
+
+    /// Open the popup to change the value written to the current column of the VALUES tab.
+    pub fn start_editing_value(&mut self) {
+        self.constraint_input = self.specified_columns.values[self.current_column].clone().unwrap_or_default();
+        self.state = AppState::Editing;
+        self.currently_editing = Some(CurrentlyEditing::Value);
+    }
+
+    /// Check a value typed for column `i` against its type and nullability.
+    pub fn validate_value(&self, i: usize, input: &str) -> Result<(), String> {
+        let column = &self.base_columns[i];
+        match SetValue::parse(input, column.data_type)? {
+            SetValue::Null if !column.nullable => Err(format!("{} is NOT NULL", column.name)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Save the value typed in the VALUES popup, leaving it empty removes the value.
+    pub fn save_value(&mut self) {
+        let input = self.constraint_input.trim().to_string();
+        if !input.is_empty() {
+            if let Err(err) = self.validate_value(self.current_column, &input) {
+                self.input_error = Some(err);
+                return;
+            }
+        }
+        self.specified_columns.values[self.current_column] = (!input.is_empty()).then_some(input);
+        self.clear_constraint();
+        self.currently_editing = None;
+    }
+
+    pub fn clear_value(&mut self) {
+        self.specified_columns.values[self.current_column] = None;
+    }
+
+    /// Value written to column `i`, values that no longer parse are kept as raw SQL.
+    pub fn set_value(&self, i: usize) -> Option<SetValue> {
+        let input = self.specified_columns.values[i].as_ref()?;
+        Some(SetValue::parse(input, self.base_columns[i].data_type).unwrap_or_else(|_| SetValue::Raw(input.clone())))
+    }
+
+    /// Whether an INSERT needs a value for column `i`, having neither a default nor NULL to fall back on.
+    pub fn value_required(&self, i: usize) -> bool {
+        let column = &self.base_columns[i];
+        !column.nullable && column.default.is_none()
+    }
+
+    /// Build the statement changing rows, checking that it has what it needs to run safely.
+    pub fn build_statement(&self) -> Result<Statement, String> {
+        let kind = self.statement;
+        if self.has_joins() {
+            return Err(format!("{} works on a single table, edit it without joined tables", kind));
+        }
+        let table = self.tables[0].name.clone();
+        let assignments: Vec<Assignment> = (0..self.column_tables.len())
+            .filter_map(|i| self.set_value(i).map(|value| Assignment { column: self.base_columns[i].name.clone(), value }))
+            .collect();
+        let where_clause = self.where_condition()?;
+
+        if matches!(kind, StatementKind::Insert | StatementKind::Merge) {
+            if let Some(i) = (0..self.column_tables.len()).find(|&i| self.value_required(i) && self.set_value(i).is_none()) {
+                return Err(format!("{} needs a value for {}, it is NOT NULL without a default", kind, self.base_columns[i].name));
+            }
+        }
+        match kind {
+            StatementKind::Select => Err("a SELECT is not a statement changing rows".to_string()),
+            StatementKind::Insert if assignments.is_empty() => Err("INSERT needs a value for a column".to_string()),
+            StatementKind::Insert => Ok(Statement::Insert { table, assignments }),
+            StatementKind::Update if assignments.is_empty() => Err("UPDATE needs a value for a column to set".to_string()),
+            StatementKind::Update | StatementKind::Delete if where_clause.is_none() => {
+                Err(format!("{} would change every row, add a WHERE condition", kind))
+            },
+            StatementKind::Update => Ok(Statement::Update { table, assignments, where_clause }),
+            StatementKind::Delete => Ok(Statement::Delete { table, where_clause }),
+            StatementKind::Merge => {
+                let keys: Vec<usize> = (0..self.column_tables.len()).filter(|&i| self.base_columns[i].primary_key).collect();
+                if keys.is_empty() {
+                    return Err(format!("MERGE keys on the primary key, {} has no primary_key column", table));
                 }
-                OrderdFlag::Desc =>  {
-                    if first_element {
-                        println!("\nORDER BY");
-                        print!("\t{} DESC", self.base_columns[i]);
-                        first_element = false;
-                    } else {
-                        print!(",\n\t{} DESC", self.base_columns[i]);
-                    }
+                if let Some(&i) = keys.iter().find(|&&i| !matches!(self.set_value(i), Some(SetValue::Literal(_) | SetValue::Raw(_)))) {
+                    return Err(format!("MERGE needs a value for the key {}", self.base_columns[i].name));
                 }
-                OrderdFlag::Off => {},
+                let keys = keys.iter().map(|&i| self.base_columns[i].name.clone()).collect();
+                Ok(Statement::Merge { table, assignments, keys })
+            },
+        }
+    }
+
+    /// SELECT of the rows the statement changes: those of its WHERE, or those holding the key
+    /// (or all the values when the table has none) of the row written by INSERT and MERGE.
+    pub fn preview_query(&self, statement: &Statement) -> Query {
+        let mut query = Query { from: TableRef { name: self.tables[0].name.clone(), alias: None }, ..Query::default() };
+        query.where_clause = match statement {
+            Statement::Update { where_clause, .. } | Statement::Delete { where_clause, .. } => where_clause.clone(),
+            Statement::Insert { assignments, .. } | Statement::Merge { assignments, .. } => {
+                let has_key = assignments.iter().any(|assignment| self.is_key(&assignment.column));
+                let children: Vec<Condition> = assignments
+                    .iter()
+                    .filter(|assignment| !has_key || self.is_key(&assignment.column))
+                    .map(|assignment| {
+                        Condition::Predicate(Predicate {
+                            expr: Expr::Column { table: None, name: assignment.column.clone() },
+                            constraint: assignment.value.constraint(),
+                        })
+                    })
+                    .collect();
+                Some(Condition::Group { operator: LogicalOperator::And, children })
+            },
+        };
+        query
+    }
+
+    /// Whether the table column of that name is part of the primary key.
+    fn is_key(&self, name: &str) -> bool {
+        self.base_columns[..self.column_tables.len()].iter().any(|column| column.name == name && column.primary_key)
+    }
+
+    /// Check that the selections make a query, which grouped queries always do.
+    pub fn check_query(&self) -> Result<(), String> {
+        if self.statement != StatementKind::Select {
+            return self.build_statement().map(|_| ());
+        }
+        if self.select_star != SelectStar::Auto && self.specified_columns.selected_columns.is_empty() && !self.is_grouping() {
+            return Err(format!("no column is selected and select_star is {}", self.select_star));
+        }
+        self.build_query().map(|_| ())
+    }
+
+    /// Build the full script (session settings followed by the query) for the current selections.
+    pub fn generate_query(&self) -> Result<String, String> {
+        self.check_query()?;
+        let mut script = String::new();
+        let preamble = self.dialect.preamble(&self.init_config);
+        if !preamble.is_empty() {
+            script.push_str(&preamble.join("\n"));
+            script.push_str("\n\n");
+        }
+        if self.statement == StatementKind::Select {
+            script.push_str(&self.build_query()?.render(self.dialect));
+        } else {
+            // The rows the statement writes to are previewed first, to run before it
+            let statement = self.build_statement()?;
+            script.push_str(self.statement.preview_comment());
+            script.push('\n');
+            script.push_str(&self.preview_query(&statement).render(self.dialect));
+            script.push_str(self.dialect.terminator());
+            script.push_str("\n\n");
+            script.push_str(&statement.render(self.dialect));
+        }
+        script.push_str(self.dialect.terminator());
+        Ok(script)
+    }
+}
+
+/// Whether the characters of `filter` appear in `label` in order, ignoring case.
+fn fuzzy_match(label: &str, filter: &str) -> bool {
+    let mut chars = label.chars().flat_map(char::to_lowercase);
+    filter
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|wanted| chars.any(|c| c == wanted))
+}
+
+/// Find an equality between the new table and one loaded before it from the declared foreign keys.
+///
+/// References from the earlier tables to the new one are preferred, so that listing a detail table
+/// before its master joins on the detail's foreign key.
+fn infer_join_condition(
+    sources: &[TableSource],
+    base_columns: &[Column],
+    column_tables: &[usize],
+    name: &str,
+    alias: &str,
+    columns: &[Column],
+) -> String {
+    for (i, column) in base_columns.iter().enumerate() {
+        if let Some(reference) = &column.references {
+            if reference.table.eq_ignore_ascii_case(name) {
+                let source = &sources[column_tables[i]];
+                return format!("{}.{} = {}.{}", source.alias, column.name, alias, reference.column);
+            }
+        }
+    }
+    for column in columns {
+        if let Some(reference) = &column.references {
+            if let Some(source) = sources.iter().find(|source| reference.table.eq_ignore_ascii_case(&source.name)) {
+                return format!("{}.{} = {}.{}", alias, column.name, source.alias, reference.column);
             }
         }
-        print!("\n;");
     }
+    String::new()
 }
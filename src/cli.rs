@@ -0,0 +1,119 @@
+use argh::FromArgs;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+use crate::{app::SelectStar, dialect::Dialect};
+
+/// Generate SQL queries from the table definitions in a tables directory.
+#[derive(FromArgs)]
+pub struct Cli {
+    /// directory holding the <TABLE>.toml definitions (default: ./tables)
+    #[argh(option, default = "PathBuf::from(\"tables\")")]
+    pub tables_dir: PathBuf,
+
+    /// SQL dialect to generate: oracle, postgres, mysql, sqlite or sqlserver
+    /// (default: the dialect of the table file, then oracle)
+    #[argh(option, from_str_fn(parse_dialect))]
+    pub dialect: Option<Dialect>,
+
+    /// when to write the SELECT list as *: auto (all or no columns selected), when-all
+    /// (all columns selected, none is an error) or never (none is an error)
+    #[argh(option, from_str_fn(parse_select_star))]
+    pub select_star: Option<SelectStar>,
+
+    /// write the generated SQL to this file instead of stdout
+    #[argh(option, short = 'o')]
+    pub output: Option<PathBuf>,
+
+    /// copy the generated SQL to the clipboard (OSC 52) instead of writing it to stdout
+    #[argh(switch, short = 'c')]
+    pub clipboard: bool,
+
+    /// write the generated SQL to stdout as well when using --output or --clipboard
+    #[argh(switch)]
+    pub stdout: bool,
+
+    #[argh(subcommand)]
+    pub command: Command,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+pub enum Command {
+    Edit(EditCommand),
+    Generate(GenerateCommand),
+    Tables(TablesCommand),
+    Columns(ColumnsCommand),
+    Ddl(DdlCommand),
+}
+
+/// Build a query interactively, joining the tables in the order given.
+#[derive(FromArgs)]
+#[argh(subcommand, name = "edit")]
+pub struct EditCommand {
+    /// reopen a saved session, by name or path to its file, instead of naming tables
+    #[argh(option)]
+    pub session: Option<String>,
+
+    /// table definition file to load in addition to the named tables, can be repeated
+    #[argh(option)]
+    pub table_file: Vec<PathBuf>,
+
+    /// names of the tables to load from the tables directory
+    #[argh(positional)]
+    pub tables: Vec<String>,
+}
+
+/// Generate the query described in a TOML or JSON spec file, without the interactive editor.
+#[derive(FromArgs)]
+#[argh(subcommand, name = "generate")]
+pub struct GenerateCommand {
+    /// query spec file, read as JSON when it ends in .json and as TOML otherwise
+    #[argh(positional)]
+    pub spec: PathBuf,
+}
+
+/// List the table definitions of the tables directory.
+#[derive(FromArgs)]
+#[argh(subcommand, name = "tables")]
+pub struct TablesCommand {}
+
+/// Show the columns of a table definition.
+#[derive(FromArgs)]
+#[argh(subcommand, name = "columns")]
+pub struct ColumnsCommand {
+    /// table definition file to show instead of a named table
+    #[argh(option)]
+    pub table_file: Option<PathBuf>,
+
+    /// name of the table in the tables directory
+    #[argh(positional)]
+    pub table: Option<String>,
+}
+
+/// Write the CREATE TABLE, CREATE INDEX and comment statements of table definitions.
+#[derive(FromArgs)]
+#[argh(subcommand, name = "ddl")]
+pub struct DdlCommand {
+    /// table definition file to create in addition to the named tables, can be repeated
+    #[argh(option)]
+    pub table_file: Vec<PathBuf>,
+
+    /// names of the tables to create, every table of the tables directory when none is given
+    #[argh(positional)]
+    pub tables: Vec<String>,
+}
+
+fn parse_dialect(value: &str) -> Result<Dialect, String> {
+    value.parse::<Dialect>().map_err(|_| {
+        let dialects: Vec<String> = Dialect::iter().map(|dialect| dialect.to_string()).collect();
+        format!("unknown dialect `{}`, expected one of {}", value, dialects.join(", "))
+    })
+}
+
+fn parse_select_star(value: &str) -> Result<SelectStar, String> {
+    value.parse::<SelectStar>().map_err(|_| {
+        let policies: Vec<String> = SelectStar::iter().map(|policy| policy.to_string()).collect();
+        format!("unknown select-star policy `{}`, expected one of {}", value, policies.join(", "))
+    })
+}
@@ -0,0 +1,22 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::{
+    env,
+    fs::OpenOptions,
+    io::{self, Write},
+};
+
+/// Put `text` on the system clipboard with the OSC 52 escape sequence.
+///
+/// The terminal does the copying, so this works over SSH as long as the terminal
+/// supports OSC 52. Inside tmux the sequence is passed through to the outer terminal.
+pub fn copy(text: &str) -> io::Result<()> {
+    let mut sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    if env::var_os("TMUX").is_some() {
+        sequence = format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"));
+    }
+    // stdout and stderr may be redirected, the controlling terminal is the one to tell
+    match OpenOptions::new().write(true).open("/dev/tty") {
+        Ok(mut tty) => tty.write_all(sequence.as_bytes()),
+        Err(_) => io::stderr().write_all(sequence.as_bytes()),
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{
+    dialect::Dialect,
+    table::{Column, DataType, ForeignKey, Index, Table},
+};
+
+/// Statements creating the tables with their indexes and comments, followed by the foreign keys
+/// so that the tables may refer to each other whatever their order.
+pub fn render(tables: &[(String, Table)], dialect: Dialect) -> Vec<String> {
+    let mut statements = Vec::new();
+    for (name, table) in tables {
+        statements.push(create_table(name, table, dialect));
+        for index in &table.indexes {
+            statements.push(create_index(name, index, dialect));
+        }
+        statements.extend(comments(name, table, dialect));
+    }
+    // SQLite cannot add constraints to an existing table, they are declared in CREATE TABLE instead
+    if dialect != Dialect::Sqlite {
+        for (name, table) in tables {
+            for column in &table.columns {
+                if let Some(reference) = &column.references {
+                    let constraint = foreign_key(name, column, reference, dialect);
+                    statements.push(format!("ALTER TABLE {} ADD {}", dialect.quote_identifier(name), constraint));
+                }
+            }
+        }
+    }
+    statements
+}
+
+fn create_table(name: &str, table: &Table, dialect: Dialect) -> String {
+    let quote = |identifier: &str| dialect.quote_identifier(identifier);
+    let mut items: Vec<String> = table.columns.iter().map(|column| column_definition(column, table, dialect)).collect();
+    let keys: Vec<String> = table.columns.iter().filter(|column| column.primary_key).map(|column| quote(&column.name)).collect();
+    if !keys.is_empty() {
+        items.push(format!("CONSTRAINT {} PRIMARY KEY ({})", quote(&format!("{}_PK", name)), keys.join(", ")));
+    }
+    if dialect == Dialect::Sqlite {
+        for column in &table.columns {
+            if let Some(reference) = &column.references {
+                items.push(foreign_key(name, column, reference, dialect));
+            }
+        }
+    }
+
+    let mut sql = format!("CREATE TABLE {} (\n\t{}\n)", quote(name), items.join(",\n\t"));
+    match (dialect, &table.comment) {
+        (Dialect::Mysql, Some(comment)) => sql.push_str(&format!(" COMMENT = {}", dialect.string_literal(comment))),
+        (Dialect::Sqlite, Some(comment)) => sql = format!("-- {}\n{}", comment.replace('\n', " "), sql),
+        _ => {},
+    }
+    sql
+}
+
+fn column_definition(column: &Column, table: &Table, dialect: Dialect) -> String {
+    let key = column.primary_key
+        || column.references.is_some()
+        || table.indexes.iter().any(|index| index.columns.contains(&column.name));
+    let data_type = if key { dialect.key_column_type(column.data_type) } else { dialect.column_type(column.data_type) };
+    let mut sql = format!("{} {}", dialect.quote_identifier(&column.name), data_type);
+    if let Some(default) = &column.default {
+        sql.push_str(&format!(" DEFAULT {}", default_value(default, column.data_type, dialect)));
+    }
+    if !column.nullable || column.primary_key {
+        sql.push_str(" NOT NULL");
+    }
+    match (dialect, &column.comment) {
+        (Dialect::Mysql, Some(comment)) => sql.push_str(&format!(" COMMENT {}", dialect.string_literal(comment))),
+        // SQLite keeps the text of CREATE TABLE, comments included, in sqlite_schema
+        (Dialect::Sqlite, Some(comment)) => sql = format!("-- {}\n\t{}", comment.replace('\n', " "), sql),
+        _ => {},
+    }
+    sql
+}
+
+/// Default of a column as written in the table definition, where SYSDATE and SYSTIMESTAMP stand for the current time.
+fn default_value(default: &str, data_type: Option<DataType>, dialect: Dialect) -> String {
+    let current_time = ["SYSDATE", "SYSTIMESTAMP"].iter().any(|name| default.trim().eq_ignore_ascii_case(name));
+    if !current_time || dialect == Dialect::Oracle {
+        return default.to_string();
+    }
+    match (dialect, data_type) {
+        (Dialect::Postgres | Dialect::Sqlite, Some(DataType::Date)) => "CURRENT_DATE".to_string(),
+        (Dialect::Mysql, Some(DataType::Date)) => "(CURRENT_DATE)".to_string(),
+        (Dialect::Sqlserver, Some(DataType::Date)) => "CAST(GETDATE() AS DATE)".to_string(),
+        _ => "CURRENT_TIMESTAMP".to_string(),
+    }
+}
+
+fn create_index(table: &str, index: &Index, dialect: Dialect) -> String {
+    let quote = |identifier: &str| dialect.quote_identifier(identifier);
+    let name = index.name.clone().unwrap_or_else(|| {
+        let suffix = if index.unique { "UK" } else { "IX" };
+        format!("{}_{}_{}", table, index.columns.join("_"), suffix)
+    });
+    let columns: Vec<String> = index.columns.iter().map(|column| quote(column)).collect();
+    let unique = if index.unique { "UNIQUE " } else { "" };
+    format!("CREATE {}INDEX {} ON {} ({})", unique, quote(&name), quote(table), columns.join(", "))
+}
+
+fn foreign_key(table: &str, column: &Column, reference: &ForeignKey, dialect: Dialect) -> String {
+    let quote = |identifier: &str| dialect.quote_identifier(identifier);
+    format!(
+        "CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({})",
+        quote(&format!("{}_{}_FK", table, column.name)),
+        quote(&column.name),
+        quote(&reference.table),
+        quote(&reference.column)
+    )
+}
+
+/// Statements commenting the table and its columns, on the dialects that do not take comments in CREATE TABLE.
+fn comments(name: &str, table: &Table, dialect: Dialect) -> Vec<String> {
+    let quote = |identifier: &str| dialect.quote_identifier(identifier);
+    let column_comments = table.columns.iter().filter_map(|column| column.comment.as_ref().map(|comment| (column, comment)));
+    match dialect {
+        Dialect::Oracle | Dialect::Postgres => {
+            let table_comment = table.comment.as_ref().map(|comment| {
+                format!("COMMENT ON TABLE {} IS {}", quote(name), dialect.string_literal(comment))
+            });
+            table_comment
+                .into_iter()
+                .chain(column_comments.map(|(column, comment)| {
+                    format!("COMMENT ON COLUMN {}.{} IS {}", quote(name), quote(&column.name), dialect.string_literal(comment))
+                }))
+                .collect()
+        },
+        Dialect::Sqlserver => {
+            // Descriptions are extended properties of the table and of its columns, in the default schema
+            let property = |comment: &str, column: Option<&str>| {
+                let mut sql = format!(
+                    "EXEC sp_addextendedproperty @name = N'MS_Description', @value = N{}, \
+                     @level0type = N'SCHEMA', @level0name = N'dbo', @level1type = N'TABLE', @level1name = N{}",
+                    dialect.string_literal(comment),
+                    dialect.string_literal(name)
+                );
+                if let Some(column) = column {
+                    sql.push_str(&format!(", @level2type = N'COLUMN', @level2name = N{}", dialect.string_literal(column)));
+                }
+                sql
+            };
+            let table_comment = table.comment.as_ref().map(|comment| property(comment, None));
+            table_comment
+                .into_iter()
+                .chain(column_comments.map(|(column, comment)| property(comment, Some(&column.name))))
+                .collect()
+        },
+        Dialect::Mysql | Dialect::Sqlite => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables() -> Vec<(String, Table)> {
+        let departments = r#"
+            columns = [{ name = "DEPARTMENT_ID", type = "NUMBER(4)", primary_key = true }]
+        "#;
+        let employees = r#"
+            comment = "People"
+            columns = [
+                { name = "EMPLOYEE_ID", type = "NUMBER(6)", primary_key = true },
+                { name = "HIRE_DATE", type = "DATE", nullable = false, default = "SYSDATE" },
+                { name = "DEPARTMENT_ID", type = "NUMBER(4)", references = "DEPARTMENTS.DEPARTMENT_ID", comment = "Works in" },
+            ]
+            indexes = [{ columns = ["HIRE_DATE"] }]
+        "#;
+        // Employees come first, so the foreign key refers to a table created after it
+        vec![
+            ("EMPLOYEES".to_string(), toml::from_str(employees).unwrap()),
+            ("DEPARTMENTS".to_string(), toml::from_str(departments).unwrap()),
+        ]
+    }
+
+    #[test]
+    fn adds_foreign_keys_after_every_table() {
+        for dialect in [Dialect::Oracle, Dialect::Postgres, Dialect::Mysql, Dialect::Sqlserver] {
+            let statements = render(&tables(), dialect);
+            assert_eq!(
+                statements.last().unwrap(),
+                "ALTER TABLE EMPLOYEES ADD CONSTRAINT EMPLOYEES_DEPARTMENT_ID_FK \
+                 FOREIGN KEY (DEPARTMENT_ID) REFERENCES DEPARTMENTS (DEPARTMENT_ID)",
+                "{}",
+                dialect
+            );
+            assert!(!statements[0].contains("FOREIGN KEY"), "{}", dialect);
+        }
+    }
+
+    #[test]
+    fn declares_foreign_keys_inline_on_sqlite() {
+        let statements = render(&tables(), Dialect::Sqlite);
+        assert!(statements.iter().all(|statement| !statement.starts_with("ALTER TABLE")));
+        assert!(statements[0].ends_with(
+            "\tCONSTRAINT EMPLOYEES_PK PRIMARY KEY (EMPLOYEE_ID),\n\
+             \tCONSTRAINT EMPLOYEES_DEPARTMENT_ID_FK FOREIGN KEY (DEPARTMENT_ID) REFERENCES DEPARTMENTS (DEPARTMENT_ID)\n)"
+        ));
+    }
+
+    #[test]
+    fn creates_tables_per_dialect() {
+        assert_eq!(
+            render(&tables(), Dialect::Oracle)[0],
+            "CREATE TABLE EMPLOYEES (\n\
+             \tEMPLOYEE_ID NUMBER(6) NOT NULL,\n\
+             \tHIRE_DATE DATE DEFAULT SYSDATE NOT NULL,\n\
+             \tDEPARTMENT_ID NUMBER(4),\n\
+             \tCONSTRAINT EMPLOYEES_PK PRIMARY KEY (EMPLOYEE_ID)\n)"
+        );
+        let default = |dialect| {
+            let statements = render(&tables(), dialect);
+            statements[0].lines().map(str::trim).find(|line| line.starts_with("HIRE_DATE")).unwrap().to_string()
+        };
+        assert_eq!(default(Dialect::Postgres), "HIRE_DATE DATE DEFAULT CURRENT_DATE NOT NULL,");
+        assert_eq!(default(Dialect::Mysql), "HIRE_DATE DATE DEFAULT (CURRENT_DATE) NOT NULL,");
+        assert_eq!(default(Dialect::Sqlserver), "HIRE_DATE DATE DEFAULT CAST(GETDATE() AS DATE) NOT NULL,");
+        assert_eq!(default(Dialect::Sqlite), "HIRE_DATE TEXT DEFAULT CURRENT_DATE NOT NULL,");
+    }
+
+    #[test]
+    fn bounds_the_text_of_key_columns() {
+        let table = r#"
+            columns = [{ name = "CODE", primary_key = true }, { name = "LABEL", type = "VARCHAR2" }, "NOTES"]
+            indexes = [{ columns = ["LABEL"] }]
+        "#;
+        let tables = vec![("TAGS".to_string(), toml::from_str(table).unwrap())];
+        assert!(render(&tables, Dialect::Mysql)[0].starts_with(
+            "CREATE TABLE TAGS (\n\tCODE VARCHAR(255) NOT NULL,\n\tLABEL VARCHAR(255),\n\tNOTES LONGTEXT,"
+        ));
+        assert!(render(&tables, Dialect::Sqlserver)[0].starts_with(
+            "CREATE TABLE TAGS (\n\tCODE NVARCHAR(450) NOT NULL,\n\tLABEL NVARCHAR(450),\n\tNOTES NVARCHAR(MAX),"
+        ));
+    }
+
+    #[test]
+    fn comments_per_dialect() {
+        let statements = render(&tables(), Dialect::Postgres);
+        assert_eq!(statements[1], "CREATE INDEX EMPLOYEES_HIRE_DATE_IX ON EMPLOYEES (HIRE_DATE)");
+        assert_eq!(statements[2], "COMMENT ON TABLE EMPLOYEES IS 'People'");
+        assert_eq!(statements[3], "COMMENT ON COLUMN EMPLOYEES.DEPARTMENT_ID IS 'Works in'");
+        // MySQL takes the comments in CREATE TABLE
+        let statements = render(&tables(), Dialect::Mysql);
+        assert!(statements[0].contains("DEPARTMENT_ID DECIMAL(4,0) COMMENT 'Works in',"));
+        assert!(statements[0].ends_with(") COMMENT = 'People'"));
+        assert!(statements.iter().all(|statement| !statement.starts_with("COMMENT")));
+        let statements = render(&tables(), Dialect::Sqlserver);
+        assert!(statements[3].starts_with("EXEC sp_addextendedproperty @name = N'MS_Description', @value = N'Works in'"));
+    }
+}
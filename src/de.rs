@@ -0,0 +1,35 @@
+use serde::de::{self, value::MapAccessDeserializer, Deserialize, Deserializer, MapAccess, Visitor};
+use std::{fmt, marker::PhantomData};
+
+/// A value written either as a plain string or as a table read into `T`.
+///
+/// Untagged enums only report that the value matched none of their variants, reading the table
+/// into its own type keeps its errors, such as the name of a misspelled field.
+pub enum StringOrMap<T> {
+    String(String),
+    Map(T),
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for StringOrMap<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct StringOrMapVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for StringOrMapVisitor<T> {
+            type Value = StringOrMap<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string or a table")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                Ok(StringOrMap::String(value.to_string()))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                T::deserialize(MapAccessDeserializer::new(map)).map(StringOrMap::Map)
+            }
+        }
+
+        deserializer.deserialize_any(StringOrMapVisitor(PhantomData))
+    }
+}
@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString};
+
+use crate::{
+    init::{InitKind, InitSetting},
+    table::DataType,
+};
+
+/// SQL engine the generated query is written for.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumIter, EnumString)]
+#[serde(rename_all = "lowercase")]
+#[strum(ascii_case_insensitive)]
+pub enum Dialect {
+    #[default]
+    #[strum(to_string = "oracle")]
+    Oracle,
+    #[serde(alias = "postgresql")]
+    #[strum(to_string = "postgres", serialize = "postgresql")]
+    Postgres,
+    #[strum(to_string = "mysql")]
+    Mysql,
+    #[strum(to_string = "sqlite")]
+    Sqlite,
+    #[serde(alias = "mssql")]
+    #[strum(to_string = "sqlserver", serialize = "mssql")]
+    Sqlserver,
+}
+
+impl Dialect {
+    /// Session settings the INIT tab starts with.
+    pub fn default_init_config(self) -> Vec<InitSetting> {
+        let config: &[(InitKind, &str, &str)] = match self {
+            Dialect::Oracle => &[
+                (InitKind::Set, "LINESIZE", "10000"),
+                (InitKind::Set, "PAGESIZE", "10000"),
+                (InitKind::Set, "NUMWIDTH", "14"),
+                (InitKind::Set, "COLSEP", "\",\""),
+                (InitKind::AlterSession, "NLS_DATE_FORMAT", "'YYYY/MM/DD_HH24:MI:SS'"),
+            ],
+            Dialect::Postgres => &[
+                (InitKind::Set, "DateStyle", "'ISO, YMD'"),
+                (InitKind::Set, "client_encoding", "'UTF8'"),
+            ],
+            Dialect::Mysql => &[
+                (InitKind::Set, "time_zone", "'+00:00'"),
+            ],
+            Dialect::Sqlite => &[
+                (InitKind::Set, "headers", "on"),
+                (InitKind::Set, "mode", "csv"),
+            ],
+            Dialect::Sqlserver => &[
+                (InitKind::Set, "NOCOUNT", "ON"),
+                (InitKind::Set, "DATEFORMAT", "ymd"),
+            ],
+        };
+        config
+            .iter()
+            .map(|(kind, key, value)| InitSetting::new(*kind, key, value))
+            .collect()
+    }
+
+    /// Render the enabled INIT settings as the statements that precede the query.
+    pub fn preamble(self, init_config: &[InitSetting]) -> Vec<String> {
+        init_config
+            .iter()
+            .filter(|setting| setting.enabled)
+            .map(|setting| self.render_setting(setting))
+            .collect()
+    }
+
+    /// Render an INIT setting according to its kind.
+    pub fn render_setting(self, setting: &InitSetting) -> String {
+        let InitSetting { kind, key, value, .. } = setting;
+        match (kind, self) {
+            (InitKind::Raw, _) => format!("{} {}", key, value).trim().to_string(),
+            (InitKind::Set, Dialect::Oracle) => format!("SET {} {}", key, value),
+            (InitKind::AlterSession, Dialect::Oracle) => format!("ALTER SESSION SET {} = {};", key, value),
+            (InitKind::ColumnFormat, Dialect::Oracle) => format!("COLUMN {} FORMAT {}", key, value),
+            (InitKind::ColumnFormat, _) => format!("-- COLUMN {} FORMAT {} is only understood by SQL*Plus", key, value),
+            (InitKind::Set | InitKind::AlterSession, Dialect::Postgres) => format!("SET {} = {};", key, value),
+            (InitKind::Set | InitKind::AlterSession, Dialect::Mysql) => format!("SET SESSION {} = {};", key, value),
+            (InitKind::Set | InitKind::AlterSession, Dialect::Sqlite) => format!(".{} {}", key, value),
+            (InitKind::Set | InitKind::AlterSession, Dialect::Sqlserver) => format!("SET {} {};", key, value),
+        }
+    }
+
+    /// Quote an identifier if it would not be read back verbatim when left bare.
+    pub fn quote_identifier(self, identifier: &str) -> String {
+        if is_regular_identifier(identifier) {
+            return identifier.to_string();
+        }
+        match self {
+            Dialect::Oracle | Dialect::Postgres | Dialect::Sqlite => {
+                format!("\"{}\"", identifier.replace('"', "\"\""))
+            },
+            Dialect::Mysql => format!("`{}`", identifier.replace('`', "``")),
+            Dialect::Sqlserver => format!("[{}]", identifier.replace(']', "]]")),
+        }
+    }
+
+    /// Check a column alias against the identifier rules of the dialect, it is quoted when needed.
+    pub fn validate_alias(self, alias: &str) -> Result<(), String> {
+        if alias.is_empty() {
+            return Err("an alias cannot be empty".to_string());
+        }
+        if alias.chars().any(char::is_control) {
+            return Err("an alias cannot contain control characters".to_string());
+        }
+        let limit = match self {
+            Dialect::Oracle => Some((alias.len(), 128, "bytes")),
+            Dialect::Postgres => Some((alias.len(), 63, "bytes")),
+            Dialect::Mysql => Some((alias.chars().count(), 256, "characters")),
+            Dialect::Sqlserver => Some((alias.chars().count(), 128, "characters")),
+            Dialect::Sqlite => None,
+        };
+        if let Some((length, max_length, unit)) = limit.filter(|(length, max_length, _)| length > max_length) {
+            return Err(format!("{} aliases are at most {} {} long, this one is {}", self, max_length, unit, length));
+        }
+        if self == Dialect::Oracle && alias.contains('"') {
+            return Err("oracle identifiers cannot contain double quotes".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether ORDER BY accepts SELECT list aliases inside expressions, not only as whole sort keys.
+    pub fn alias_in_order_expressions(self) -> bool {
+        !matches!(self, Dialect::Postgres | Dialect::Sqlserver)
+    }
+
+    /// Quote a string literal.
+    pub fn string_literal(self, text: &str) -> String {
+        let escaped = match self {
+            // MySQL also treats backslashes as escape characters inside strings
+            Dialect::Mysql => text.replace('\\', "\\\\").replace('\'', "''"),
+            _ => text.replace('\'', "''"),
+        };
+        format!("'{}'", escaped)
+    }
+
+    /// Write a date given as YYYY-MM-DD as a literal of the dialect.
+    pub fn date_literal(self, date: &str) -> String {
+        match self {
+            Dialect::Oracle | Dialect::Postgres | Dialect::Mysql => format!("DATE '{}'", date),
+            // SQLite stores dates as text and SQL Server converts ISO strings implicitly
+            Dialect::Sqlite | Dialect::Sqlserver => format!("'{}'", date),
+        }
+    }
+
+    /// Write a timestamp given as YYYY-MM-DD HH:MI:SS as a literal of the dialect.
+    pub fn timestamp_literal(self, timestamp: &str) -> String {
+        match self {
+            Dialect::Oracle | Dialect::Postgres | Dialect::Mysql => format!("TIMESTAMP '{}'", timestamp),
+            Dialect::Sqlite => format!("'{}'", timestamp),
+            // The T separator keeps the format independent of the DATEFORMAT setting
+            Dialect::Sqlserver => format!("'{}'", timestamp.replacen(' ', "T", 1)),
+        }
+    }
+
+    /// Whether ORDER BY understands NULLS FIRST and NULLS LAST.
+    pub fn supports_nulls_order(self) -> bool {
+        matches!(self, Dialect::Oracle | Dialect::Postgres | Dialect::Sqlite)
+    }
+
+    /// Whether SELECT accepts DISTINCT ON, keeping the first row of each set of keys.
+    pub fn supports_distinct_on(self) -> bool {
+        self == Dialect::Postgres
+    }
+
+    /// Refer to a parameter the way the command line client of the dialect substitutes it.
+    pub fn placeholder(self, name: &str) -> String {
+        match self {
+            Dialect::Oracle | Dialect::Postgres | Dialect::Sqlite => format!(":{}", name),
+            Dialect::Mysql | Dialect::Sqlserver => format!("@{}", name),
+        }
+    }
+
+    /// Column type of the dialect closest to a type of the table definition, untyped columns hold text.
+    pub fn column_type(self, data_type: Option<DataType>) -> String {
+        let size = |name: &str, size: Option<u32>| size.map_or(name.to_string(), |size| format!("{}({})", name, size));
+        match (self, data_type) {
+            (Dialect::Oracle, Some(DataType::Varchar2(None)) | None) => "VARCHAR2(4000)".to_string(),
+            (Dialect::Oracle, Some(data_type)) => data_type.to_string(),
+            (Dialect::Postgres | Dialect::Mysql | Dialect::Sqlserver, Some(DataType::Number { precision: Some(precision), scale })) => {
+                let name = if self == Dialect::Postgres { "NUMERIC" } else { "DECIMAL" };
+                format!("{}({},{})", name, precision, scale.unwrap_or(0))
+            },
+            (Dialect::Postgres, Some(DataType::Number { precision: None, .. })) => "NUMERIC".to_string(),
+            (Dialect::Mysql | Dialect::Sqlserver, Some(DataType::Number { precision: None, .. })) => "DECIMAL(38,10)".to_string(),
+            (Dialect::Postgres | Dialect::Sqlite, Some(DataType::Integer)) => "INTEGER".to_string(),
+            (Dialect::Mysql | Dialect::Sqlserver, Some(DataType::Integer)) => "INT".to_string(),
+            (Dialect::Postgres, Some(DataType::Float)) => "DOUBLE PRECISION".to_string(),
+            (Dialect::Mysql, Some(DataType::Float)) => "DOUBLE".to_string(),
+            (Dialect::Sqlserver, Some(DataType::Float)) => "FLOAT".to_string(),
+            (Dialect::Postgres | Dialect::Mysql | Dialect::Sqlserver, Some(DataType::Char(length))) => size("CHAR", length),
+            (Dialect::Postgres, Some(DataType::Varchar2(length))) => size("VARCHAR", length),
+            (Dialect::Mysql, Some(DataType::Varchar2(Some(length)))) => size("VARCHAR", Some(length)),
+            (Dialect::Sqlserver, Some(DataType::Varchar2(Some(length)))) => size("NVARCHAR", Some(length)),
+            (Dialect::Postgres | Dialect::Mysql | Dialect::Sqlserver, Some(DataType::Date)) => "DATE".to_string(),
+            (Dialect::Postgres, Some(DataType::Timestamp)) => "TIMESTAMP".to_string(),
+            // TIMESTAMP stops in 2038 on MySQL and is a row version on SQL Server
+            (Dialect::Mysql, Some(DataType::Timestamp)) => "DATETIME".to_string(),
+            (Dialect::Sqlserver, Some(DataType::Timestamp)) => "DATETIME2".to_string(),
+            (Dialect::Postgres, Some(DataType::Clob) | None) => "TEXT".to_string(),
+            (Dialect::Mysql, Some(DataType::Varchar2(None) | DataType::Clob) | None) => "LONGTEXT".to_string(),
+            (Dialect::Sqlserver, Some(DataType::Varchar2(None) | DataType::Clob) | None) => "NVARCHAR(MAX)".to_string(),
+            (Dialect::Postgres, Some(DataType::Blob)) => "BYTEA".to_string(),
+            (Dialect::Mysql, Some(DataType::Blob)) => "LONGBLOB".to_string(),
+            (Dialect::Sqlserver, Some(DataType::Blob)) => "VARBINARY(MAX)".to_string(),
+            // SQLite only knows type affinities, dates are stored as ISO 8601 text
+            (Dialect::Sqlite, Some(DataType::Number { .. })) => "NUMERIC".to_string(),
+            (Dialect::Sqlite, Some(DataType::Float)) => "REAL".to_string(),
+            (Dialect::Sqlite, Some(DataType::Blob)) => "BLOB".to_string(),
+            (Dialect::Sqlite, _) => "TEXT".to_string(),
+        }
+    }
+
+    /// Column type of a key or indexed column, whose text must fit in an index key where the text types are unbounded.
+    pub fn key_column_type(self, data_type: Option<DataType>) -> String {
+        match (self, data_type) {
+            (Dialect::Mysql, Some(DataType::Varchar2(None)) | None) => "VARCHAR(255)".to_string(),
+            // 900 bytes is the largest clustered index key of SQL Server
+            (Dialect::Sqlserver, Some(DataType::Varchar2(None)) | None) => "NVARCHAR(450)".to_string(),
+            _ => self.column_type(data_type),
+        }
+    }
+
+    /// Text that ends the statement, appended after the last clause.
+    pub fn terminator(self) -> &'static str {
+        match self {
+            // SQL*Plus runs the buffer on a lone `;`, which keeps the last line copy-paste friendly.
+            Dialect::Oracle => "\n;",
+            _ => ";",
+        }
+    }
+}
+
+/// Whether `identifier` is safe to emit unquoted in every supported dialect.
+fn is_regular_identifier(identifier: &str) -> bool {
+    let mut chars = identifier.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    (first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !RESERVED_WORDS.contains(&identifier.to_ascii_uppercase().as_str())
+}
+
+/// Keywords reserved by at least one supported dialect that commonly collide with column names.
+const RESERVED_WORDS: &[&str] = &[
+    "ACCESS", "ADD", "ALL", "ALTER", "AND", "ANY", "AS", "ASC", "BETWEEN", "BY", "CASE", "CHECK",
+    "COLUMN", "COMMENT", "CREATE", "CURRENT", "DATE", "DEFAULT", "DELETE", "DESC", "DISTINCT",
+    "DROP", "ELSE", "END", "EXISTS", "FILE", "FOR", "FROM", "GRANT", "GROUP", "HAVING", "IN",
+    "INDEX", "INSERT", "INTERVAL", "INTO", "IS", "JOIN", "KEY", "LEVEL", "LIKE", "LIMIT", "NOT",
+    "NULL", "NUMBER", "OF", "ON", "OPTION", "OR", "ORDER", "PRIMARY", "RANGE", "ROW", "ROWID",
+    "ROWNUM", "ROWS", "SELECT", "SESSION", "SET", "SIZE", "TABLE", "THEN", "TO", "UID", "UNION",
+    "UNIQUE", "UPDATE", "USER", "VALUES", "VIEW", "WHEN", "WHERE", "WITH",
+];
@@ -0,0 +1,90 @@
+use crate::dialect::Dialect;
+
+/// A function offered when writing an expression column, `{}` stands for the column it is applied to.
+pub struct Function {
+    pub name: &'static str,
+    pub template: &'static str,
+}
+
+impl Function {
+    const fn new(name: &'static str, template: &'static str) -> Function {
+        Function { name, template }
+    }
+
+    /// Apply the function to `column`.
+    pub fn apply(&self, column: &str) -> String {
+        self.template.replace("{}", column)
+    }
+}
+
+const ORACLE_FUNCTIONS: &[Function] = &[
+    Function::new("NVL", "NVL({}, 0)"),
+    Function::new("TO_CHAR", "TO_CHAR({}, 'YYYY-MM')"),
+    Function::new("TRUNC", "TRUNC({}, 'MM')"),
+    Function::new("EXTRACT", "EXTRACT(YEAR FROM {})"),
+    Function::new("ROUND", "ROUND({}, 2)"),
+    Function::new("UPPER", "UPPER({})"),
+    Function::new("LOWER", "LOWER({})"),
+    Function::new("SUBSTR", "SUBSTR({}, 1, 10)"),
+    Function::new("LENGTH", "LENGTH({})"),
+    Function::new("CASE", "CASE WHEN {} IS NULL THEN 'N' ELSE 'Y' END"),
+];
+
+const POSTGRES_FUNCTIONS: &[Function] = &[
+    Function::new("COALESCE", "COALESCE({}, 0)"),
+    Function::new("TO_CHAR", "TO_CHAR({}, 'YYYY-MM')"),
+    Function::new("DATE_TRUNC", "DATE_TRUNC('month', {})"),
+    Function::new("EXTRACT", "EXTRACT(YEAR FROM {})"),
+    Function::new("ROUND", "ROUND({}, 2)"),
+    Function::new("UPPER", "UPPER({})"),
+    Function::new("LOWER", "LOWER({})"),
+    Function::new("SUBSTRING", "SUBSTRING({} FROM 1 FOR 10)"),
+    Function::new("LENGTH", "LENGTH({})"),
+    Function::new("CASE", "CASE WHEN {} IS NULL THEN 'N' ELSE 'Y' END"),
+];
+
+const MYSQL_FUNCTIONS: &[Function] = &[
+    Function::new("IFNULL", "IFNULL({}, 0)"),
+    Function::new("DATE_FORMAT", "DATE_FORMAT({}, '%Y-%m')"),
+    Function::new("YEAR", "YEAR({})"),
+    Function::new("ROUND", "ROUND({}, 2)"),
+    Function::new("UPPER", "UPPER({})"),
+    Function::new("LOWER", "LOWER({})"),
+    Function::new("SUBSTRING", "SUBSTRING({}, 1, 10)"),
+    Function::new("CHAR_LENGTH", "CHAR_LENGTH({})"),
+    Function::new("CASE", "CASE WHEN {} IS NULL THEN 'N' ELSE 'Y' END"),
+];
+
+const SQLITE_FUNCTIONS: &[Function] = &[
+    Function::new("IFNULL", "IFNULL({}, 0)"),
+    Function::new("STRFTIME", "STRFTIME('%Y-%m', {})"),
+    Function::new("ROUND", "ROUND({}, 2)"),
+    Function::new("UPPER", "UPPER({})"),
+    Function::new("LOWER", "LOWER({})"),
+    Function::new("SUBSTR", "SUBSTR({}, 1, 10)"),
+    Function::new("LENGTH", "LENGTH({})"),
+    Function::new("CASE", "CASE WHEN {} IS NULL THEN 'N' ELSE 'Y' END"),
+];
+
+const SQLSERVER_FUNCTIONS: &[Function] = &[
+    Function::new("ISNULL", "ISNULL({}, 0)"),
+    Function::new("FORMAT", "FORMAT({}, 'yyyy-MM')"),
+    Function::new("YEAR", "YEAR({})"),
+    Function::new("ROUND", "ROUND({}, 2)"),
+    Function::new("UPPER", "UPPER({})"),
+    Function::new("LOWER", "LOWER({})"),
+    Function::new("SUBSTRING", "SUBSTRING({}, 1, 10)"),
+    Function::new("LEN", "LEN({})"),
+    Function::new("CASE", "CASE WHEN {} IS NULL THEN 'N' ELSE 'Y' END"),
+];
+
+/// Functions offered for the dialect, any other SQL can still be typed.
+pub fn catalogue(dialect: Dialect) -> &'static [Function] {
+    match dialect {
+        Dialect::Oracle => ORACLE_FUNCTIONS,
+        Dialect::Postgres => POSTGRES_FUNCTIONS,
+        Dialect::Mysql => MYSQL_FUNCTIONS,
+        Dialect::Sqlite => SQLITE_FUNCTIONS,
+        Dialect::Sqlserver => SQLSERVER_FUNCTIONS,
+    }
+}
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use std::{env, error::Error, fs, io, path::PathBuf};
+use strum::FromRepr;
+
+use crate::dialect::Dialect;
+
+/// How an INIT setting is turned into a statement.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, FromRepr)]
+#[serde(rename_all = "snake_case")]
+pub enum InitKind {
+    /// Client setting: `SET key value` in SQL*Plus, the session setting statement elsewhere
+    #[default]
+    Set,
+    /// `ALTER SESSION SET key = value`
+    AlterSession,
+    /// SQL*Plus `COLUMN key FORMAT value`
+    ColumnFormat,
+    /// `key value` written as is
+    Raw,
+}
+
+impl InitKind {
+    /// Get the next kind, wrapping around to the first one.
+    pub fn next(self) -> Self {
+        Self::from_repr(self as usize + 1).unwrap_or_default()
+    }
+}
+
+/// A session setting of the INIT tab.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InitSetting {
+    pub kind: InitKind,
+    pub key: String,
+    pub value: String,
+    pub enabled: bool,
+}
+
+impl InitSetting {
+    pub fn new(kind: InitKind, key: &str, value: &str) -> InitSetting {
+        InitSetting {
+            kind,
+            key: key.to_string(),
+            value: value.to_string(),
+            enabled: true,
+        }
+    }
+}
+
+/// Values accepted by a known setting.
+pub enum ValidValues {
+    Any,
+    Number,
+    OneOf(&'static [&'static str]),
+}
+
+/// A setting known to the INIT tab, offered when adding settings and used to check values.
+pub struct KnownSetting {
+    pub kind: InitKind,
+    pub key: &'static str,
+    pub default: &'static str,
+    pub values: ValidValues,
+}
+
+impl KnownSetting {
+    const fn new(key: &'static str, default: &'static str, values: ValidValues) -> KnownSetting {
+        KnownSetting { kind: InitKind::Set, key, default, values }
+    }
+
+    const fn session(key: &'static str, default: &'static str, values: ValidValues) -> KnownSetting {
+        KnownSetting { kind: InitKind::AlterSession, key, default, values }
+    }
+
+    /// Check `value` against the accepted values, returning it in canonical form.
+    pub fn validate(&self, value: &str) -> Result<String, String> {
+        match &self.values {
+            ValidValues::Any => Ok(value.to_string()),
+            ValidValues::Number => match value.parse::<u32>() {
+                Ok(_) => Ok(value.to_string()),
+                Err(_) => Err(format!("{} takes a number", self.key)),
+            },
+            ValidValues::OneOf(values) => match values.iter().find(|valid| valid.eq_ignore_ascii_case(value)) {
+                Some(valid) => Ok(valid.to_string()),
+                None => Err(format!("{} takes one of {}", self.key, values.join(", "))),
+            },
+        }
+    }
+}
+
+const ON_OFF: ValidValues = ValidValues::OneOf(&["ON", "OFF"]);
+
+/// SQL*Plus SET options that are useful when spooling query results, and the session
+/// parameters that change how values are displayed.
+const SQLPLUS_SETTINGS: &[KnownSetting] = &[
+    KnownSetting::new("ARRAYSIZE", "15", ValidValues::Number),
+    KnownSetting::new("AUTOTRACE", "OFF", ValidValues::OneOf(&["OFF", "ON", "TRACEONLY"])),
+    KnownSetting::new("COLSEP", "\",\"", ValidValues::Any),
+    KnownSetting::new("ECHO", "OFF", ON_OFF),
+    KnownSetting::new("FEEDBACK", "OFF", ON_OFF),
+    KnownSetting::new("HEADING", "ON", ON_OFF),
+    KnownSetting::new("LINESIZE", "10000", ValidValues::Number),
+    KnownSetting::new("LONG", "80", ValidValues::Number),
+    KnownSetting::new("MARKUP", "CSV ON", ValidValues::Any),
+    KnownSetting::new("NEWPAGE", "NONE", ValidValues::Any),
+    KnownSetting::new("NULL", "\"\"", ValidValues::Any),
+    KnownSetting::new("NUMFORMAT", "\"\"", ValidValues::Any),
+    KnownSetting::new("NUMWIDTH", "14", ValidValues::Number),
+    KnownSetting::new("PAGESIZE", "10000", ValidValues::Number),
+    KnownSetting::new("SERVEROUTPUT", "OFF", ON_OFF),
+    KnownSetting::new("TAB", "OFF", ON_OFF),
+    KnownSetting::new("TERMOUT", "ON", ON_OFF),
+    KnownSetting::new("TIMING", "OFF", ON_OFF),
+    KnownSetting::new("TRIMOUT", "ON", ON_OFF),
+    KnownSetting::new("TRIMSPOOL", "ON", ON_OFF),
+    KnownSetting::new("UNDERLINE", "OFF", ON_OFF),
+    KnownSetting::new("VERIFY", "OFF", ON_OFF),
+    KnownSetting::new("WRAP", "OFF", ON_OFF),
+    KnownSetting::session("CURRENT_SCHEMA", "", ValidValues::Any),
+    KnownSetting::session("NLS_DATE_FORMAT", "'YYYY/MM/DD_HH24:MI:SS'", ValidValues::Any),
+    KnownSetting::session("NLS_LANGUAGE", "'AMERICAN'", ValidValues::Any),
+    KnownSetting::session("NLS_NUMERIC_CHARACTERS", "'.,'", ValidValues::Any),
+    KnownSetting::session("NLS_TERRITORY", "'AMERICA'", ValidValues::Any),
+    KnownSetting::session("NLS_TIMESTAMP_FORMAT", "'YYYY/MM/DD_HH24:MI:SS.FF'", ValidValues::Any),
+    KnownSetting::session("TIME_ZONE", "'+00:00'", ValidValues::Any),
+];
+
+/// Settings known for the dialect, other keys are accepted with any value.
+pub fn catalogue(dialect: Dialect) -> &'static [KnownSetting] {
+    match dialect {
+        Dialect::Oracle => SQLPLUS_SETTINGS,
+        _ => &[],
+    }
+}
+
+/// Look up a known setting by key.
+pub fn find_known(dialect: Dialect, key: &str) -> Option<&'static KnownSetting> {
+    catalogue(dialect).iter().find(|known| known.key.eq_ignore_ascii_case(key))
+}
+
+#[derive(Serialize, Deserialize)]
+struct SettingsFile<T> {
+    settings: Vec<T>,
+}
+
+/// A setting as written in a file, the kind of known settings can be left out
+/// (settings saved before they had a kind do not have one).
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SettingSpec {
+    pub kind: Option<InitKind>,
+    pub key: String,
+    pub value: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl SettingSpec {
+    pub fn into_setting(self, dialect: Dialect) -> InitSetting {
+        InitSetting {
+            kind: self.kind.unwrap_or_else(|| find_known(dialect, &self.key).map_or(InitKind::Set, |known| known.kind)),
+            key: self.key,
+            value: self.value,
+            enabled: self.enabled,
+        }
+    }
+}
+
+/// Directory the settings and sessions are saved in,
+/// `$XDG_CONFIG_HOME/sql-generator` or `~/.config/sql-generator`.
+pub fn config_dir() -> Option<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_dir.join("sql-generator"))
+}
+
+/// File the INIT settings of the dialect are saved to, `<config dir>/<dialect>.toml`.
+fn settings_path(dialect: Dialect) -> Option<PathBuf> {
+    Some(config_dir()?.join(format!("{}.toml", dialect)))
+}
+
+/// Load the settings saved by a previous session, or the dialect defaults.
+pub fn load_settings(dialect: Dialect) -> Result<Vec<InitSetting>, Box<dyn Error>> {
+    let Some(path) = settings_path(dialect) else {
+        return Ok(dialect.default_init_config());
+    };
+    match fs::read_to_string(&path) {
+        Ok(str) => {
+            let file: SettingsFile<SettingSpec> =
+                toml::from_str(&str).map_err(|err| format!("{}: {}", path.display(), err))?;
+            Ok(file.settings.into_iter().map(|spec| spec.into_setting(dialect)).collect())
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(dialect.default_init_config()),
+        Err(err) => Err(format!("{}: {}", path.display(), err).into()),
+    }
+}
+
+/// Save the settings so that the next session starts with them.
+pub fn save_settings(dialect: Dialect, settings: &[InitSetting]) -> Result<(), Box<dyn Error>> {
+    let Some(path) = settings_path(dialect) else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let str = toml::to_string(&SettingsFile { settings: settings.to_vec() })?;
+    fs::write(&path, str).map_err(|err| format!("{}: {}", path.display(), err))?;
+    Ok(())
+}
@@ -1,11 +1,12 @@
-use std::{error::Error, io};
-use std::env;
+use std::{error::Error, io::{self, Write}};
+use std::path::{Path, PathBuf};
+use std::process;
 
-use app::{CurrentTab, CurrentlyEditing};
+use app::CurrentTab;
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     crossterm::{
-        event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+        event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
         execute,
         terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     },
@@ -13,35 +14,97 @@ use ratatui::{
 };
 
 mod app;
+mod cli;
+mod clipboard;
+mod de;
+mod ddl;
+mod dialect;
+mod functions;
+mod init;
+mod query;
+mod spec;
+mod table;
 mod ui;
 use crate::{
     app::{
         App,
         AppState,
-        Table,
-        SelectedFlag,
-        OrderdFlag,
     },
+    cli::{Cli, Command, ColumnsCommand, DdlCommand, EditCommand, GenerateCommand},
+    spec::QuerySpec,
+    table::Table,
     ui::ui,
 };
 
 use std::fs::{self};
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let args: Vec<String> = env::args().collect();
-    let table_name = &args[1];
-    let filepath = format!("./tables/{}.toml", table_name);
+fn main() {
+    let cli: Cli = argh::from_env();
+    if let Err(err) = run(&cli) {
+        eprintln!("error: {err}");
+        process::exit(1);
+    }
+}
+
+fn run(cli: &Cli) -> Result<(), Box<dyn Error>> {
+    match &cli.command {
+        Command::Edit(command) => edit(cli, command),
+        Command::Generate(command) => generate(cli, command),
+        Command::Tables(_) => list_tables(cli),
+        Command::Columns(command) => show_columns(cli, command),
+        Command::Ddl(command) => write_ddl(cli, command),
+    }
+}
+
+/// Run the interactive editor and write the resulting query.
+fn edit(cli: &Cli, command: &EditCommand) -> Result<(), Box<dyn Error>> {
+    let session = match &command.session {
+        Some(_) if !command.tables.is_empty() || !command.table_file.is_empty() => {
+            return Err("a session names its own tables, do not name tables with --session".into());
+        },
+        Some(name) => Some(QuerySpec::load(&spec::session_path(name)?)?),
+        None => None,
+    };
+    let table_names = session.as_ref().map_or(&command.tables, |spec| &spec.tables);
+    let table_files = session.as_ref().map_or(&command.table_file, |spec| &spec.table_files);
+
+    let tables = load_tables(&cli.tables_dir, table_names, table_files)?;
+    if tables.is_empty() {
+        return Err("no table given, name a table of the tables directory or pass --table-file".into());
+    }
+
+    let dialect = cli.dialect
+        .or(session.as_ref().and_then(|spec| spec.dialect))
+        .or_else(|| tables.iter().find_map(|(_, table)| table.dialect))
+        .unwrap_or_default();
+    // The INIT settings of a session are saved with it, not in the settings of the dialect
+    let session_init = session.as_ref().and_then(|spec| spec.init.as_ref());
+    let init_config = match session_init {
+        Some(settings) => settings.iter().map(|setting| setting.clone().into_setting(dialect)).collect(),
+        None => init::load_settings(dialect)?,
+    };
+    let save_init = session_init.is_none();
+    let mut app = App::new(tables, dialect, init_config);
+    set_table_files(&mut app, table_files);
+    if let Some(spec) = &session {
+        let orphans = spec.apply(&mut app);
+        if !orphans.is_empty() {
+            app.notice = Some(format!(
+                "Some choices of the session no longer match the tables and were left out:\n{}",
+                orphans.join("\n")
+            ));
+        }
+        app.session_name = command.session.clone();
+    }
+    if let Some(select_star) = cli.select_star {
+        app.select_star = select_star;
+    }
 
     enable_raw_mode()?;
     let mut stderr = io::stderr();
     execute!(stderr, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stderr);
     let mut terminal = Terminal::new(backend)?;
-    
-    let str = fs::read_to_string(filepath)?;
-    let table: Table = toml::from_str(&str)?;
-
-    let mut app = App::new(table);
 
     let res = run_app(&mut terminal, &mut app);
     
@@ -53,13 +116,175 @@ fn main() -> Result<(), Box<dyn Error>> {
     )?;
     terminal.show_cursor()?;
     
-    if let Ok(()) = res {
-        app.generate_query(table_name);
-    } else if let Err(err) = res {
-        println!("{err:?}");
+    if app.init_config_changed && save_init {
+        if let Err(err) = init::save_settings(app.dialect, &app.init_config) {
+            eprintln!("Could not save the INIT settings: {err}");
+        }
     }
 
-    
+    res?;
+    for warning in app.query_warnings() {
+        eprintln!("warning: {warning}");
+    }
+    write_output(cli, &app.generate_query()?)
+}
+
+/// Generate the query of a spec file and write it.
+fn generate(cli: &Cli, command: &GenerateCommand) -> Result<(), Box<dyn Error>> {
+    let spec = QuerySpec::load(&command.spec)?;
+    let tables = load_tables(&cli.tables_dir, &spec.tables, &spec.table_files)?;
+    if tables.is_empty() {
+        return Err(format!("{}: no table given in `tables` or `table_files`", command.spec.display()).into());
+    }
+
+    let dialect = cli.dialect
+        .or(spec.dialect)
+        .or_else(|| tables.iter().find_map(|(_, table)| table.dialect))
+        .unwrap_or_default();
+    // A spec gives the same script on every machine, the saved settings of the editor are left out
+    let init_config = match &spec.init {
+        Some(settings) => settings.iter().map(|setting| setting.clone().into_setting(dialect)).collect(),
+        None => dialect.default_init_config(),
+    };
+    let mut app = App::new(tables, dialect, init_config);
+    set_table_files(&mut app, &spec.table_files);
+
+    let errors = spec.apply(&mut app);
+    if !errors.is_empty() {
+        return Err(format!("invalid query spec {}:\n  {}", command.spec.display(), errors.join("\n  ")).into());
+    }
+    if let Some(select_star) = cli.select_star {
+        app.select_star = select_star;
+    }
+    for warning in app.query_warnings() {
+        eprintln!("warning: {warning}");
+    }
+    write_output(cli, &app.generate_query()?)
+}
+
+/// List the table definitions of the tables directory.
+fn list_tables(cli: &Cli) -> Result<(), Box<dyn Error>> {
+    write_output(cli, &table_names(&cli.tables_dir)?.join("\n"))
+}
+
+/// Write the DDL of the named tables, or of the whole tables directory.
+fn write_ddl(cli: &Cli, command: &DdlCommand) -> Result<(), Box<dyn Error>> {
+    let table_names = if command.tables.is_empty() && command.table_file.is_empty() {
+        table_names(&cli.tables_dir)?
+    } else {
+        command.tables.clone()
+    };
+    let mut tables = Vec::<(String, Table)>::new();
+    for table_name in &table_names {
+        tables.push((table_name.clone(), load_named_table(&cli.tables_dir, table_name)?));
+    }
+    for path in &command.table_file {
+        tables.push((table_name_of(path), Table::load(path)?));
+    }
+
+    let dialect = cli.dialect
+        .or_else(|| tables.iter().find_map(|(_, table)| table.dialect))
+        .unwrap_or_default();
+    let statements: Vec<String> = ddl::render(&tables, dialect)
+        .into_iter()
+        .map(|statement| format!("{}{}", statement, dialect.terminator()))
+        .collect();
+    write_output(cli, &statements.join("\n\n"))
+}
+
+/// Names of the table definitions of the tables directory, sorted.
+fn table_names(tables_dir: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let entries = fs::read_dir(tables_dir)
+        .map_err(|err| format!("could not read the tables directory {}: {}", tables_dir.display(), err))?;
+    let mut names = Vec::<String>::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "toml") {
+            names.push(table_name_of(&path));
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Show the columns of a table definition, one per line.
+fn show_columns(cli: &Cli, command: &ColumnsCommand) -> Result<(), Box<dyn Error>> {
+    let table = match (&command.table, &command.table_file) {
+        (Some(table_name), None) => load_named_table(&cli.tables_dir, table_name)?,
+        (None, Some(path)) => Table::load(path)?,
+        _ => return Err("name a table of the tables directory or pass --table-file, but not both".into()),
+    };
+    let lines: Vec<String> = table
+        .columns
+        .iter()
+        .map(|column| {
+            let mut line = column.name.clone();
+            if let Some(data_type) = column.data_type {
+                line.push_str(&format!(" {}", data_type));
+            }
+            if column.primary_key {
+                line.push_str(" PRIMARY KEY");
+            } else if !column.nullable {
+                line.push_str(" NOT NULL");
+            }
+            line
+        })
+        .collect();
+    write_output(cli, &lines.join("\n"))
+}
+
+/// Load `<tables_dir>/<table_name>.toml`.
+fn load_named_table(tables_dir: &Path, table_name: &str) -> Result<Table, Box<dyn Error>> {
+    let path = tables_dir.join(format!("{}.toml", table_name));
+    if !path.exists() {
+        return Err(format!("table `{}` not found, expected {}", table_name, path.display()).into());
+    }
+    Ok(Table::load(&path)?)
+}
+
+/// Load the named tables of the tables directory, then the tables of the definition files.
+fn load_tables(tables_dir: &Path, table_names: &[String], table_files: &[PathBuf]) -> Result<Vec<(String, Table)>, Box<dyn Error>> {
+    let mut tables = Vec::<(String, Table)>::new();
+    for table_name in table_names {
+        tables.push((table_name.clone(), load_named_table(tables_dir, table_name)?));
+    }
+    for path in table_files {
+        tables.push((table_name_of(path), Table::load(path)?));
+    }
+    Ok(tables)
+}
+
+/// Remember the definition files of the last tables of `app`, so that a saved session can read them again.
+fn set_table_files(app: &mut App, table_files: &[PathBuf]) {
+    let named = app.tables.len() - table_files.len();
+    for (source, path) in app.tables[named..].iter_mut().zip(table_files) {
+        source.file = Some(fs::canonicalize(path).unwrap_or_else(|_| path.clone()));
+    }
+}
+
+/// Name of the table defined in `path`, taken from the file name.
+fn table_name_of(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Write `text` to the output file and the clipboard as asked, or to stdout if neither was.
+fn write_output(cli: &Cli, text: &str) -> Result<(), Box<dyn Error>> {
+    if cli.clipboard {
+        clipboard::copy(text).map_err(|err| format!("could not copy to the clipboard: {}", err))?;
+    }
+    if let Some(path) = &cli.output {
+        fs::write(path, format!("{}\n", text))
+            .map_err(|err| format!("could not write {}: {}", path.display(), err))?;
+    }
+    if cli.stdout || (cli.output.is_none() && !cli.clipboard) {
+        match writeln!(io::stdout(), "{}", text) {
+            // the reader of a pipe may stop early, as `head` does
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => return Err(err.into()),
+            _ => {},
+        }
+    }
     Ok(())
 }
 
@@ -71,70 +296,175 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<
             if key.kind == event::KeyEventKind::Release {
                 continue;
             }
+            // any key dismisses the notice
+            if app.notice.take().is_some() {
+                // unless it asks whether to quit anyway
+                if std::mem::take(&mut app.quit_warned) && key.code == KeyCode::Char('q') {
+                    app.state = AppState::Quitting;
+                    return Ok(());
+                }
+                continue;
+            }
             match app.state {
                 AppState::Running => match key.code {
                     KeyCode::Char('l') | KeyCode::Right => app.next_tab(),
                     KeyCode::Char('h') | KeyCode::Left => app.previous_tab(),
+                    KeyCode::Esc if !app.filter.is_empty() => app.clear_filter(),
                     KeyCode::Char('q') | KeyCode::Esc => app.quit(),
+                    KeyCode::Char('/') => app.start_search(),
                     KeyCode::Char('j') | KeyCode::Down => app.next_column(),
                     KeyCode::Char('k') | KeyCode::Up => app.previous_column(),
+                    KeyCode::PageDown => app.page_down(),
+                    KeyCode::PageUp => app.page_up(),
+                    KeyCode::Home => app.first_column(),
+                    KeyCode::End => app.last_column(),
+                    KeyCode::Char('s') => app.start_saving_session(),
+                    KeyCode::Char('p') => app.toggle_preview(),
+                    KeyCode::Char(']') => app.scroll_preview(true),
+                    KeyCode::Char('[') => app.scroll_preview(false),
+                    KeyCode::Char('y') => app.copy_query(),
+                    KeyCode::Char('m') => app.cycle_statement(),
                     KeyCode::Enter => match app.current_tab {
-                        CurrentTab::Init => {}
-                        CurrentTab::Select => {
-                            let current_column = app.current_column;
-                            match app.specified_columns.selected_columns[current_column] {
-                                SelectedFlag::Selected => app.specified_columns.selected_columns[current_column] = SelectedFlag::NotSelected,
-                                SelectedFlag::NotSelected => app.specified_columns.selected_columns[current_column] = SelectedFlag::Selected,
-                            }
-                        },
-                        CurrentTab::OrderBy => {
-                            let current_column = app.current_column;
-                            match app.specified_columns.ordered_columns[current_column] {
-                                OrderdFlag::Asc => app.specified_columns.ordered_columns[current_column] = OrderdFlag::Desc,
-                                OrderdFlag::Desc => app.specified_columns.ordered_columns[current_column] = OrderdFlag::Off,
-                                OrderdFlag::Off => app.specified_columns.ordered_columns[current_column] = OrderdFlag::Asc,
-                            }
-                        },
-                        CurrentTab::Where => {}
+                        CurrentTab::Init => app.toggle_init_setting(),
+                        CurrentTab::From => app.toggle_join_kind(),
+                        CurrentTab::Select => app.toggle_selected(),
+                        CurrentTab::OrderBy => app.toggle_order(),
+                        CurrentTab::Where => app.toggle_where_operator(),
+                        CurrentTab::GroupBy => app.toggle_group_flag(),
+                        CurrentTab::Limit => app.toggle_limit_setting(),
+                        CurrentTab::Values => app.start_editing_value(),
                     }
                     KeyCode::Char('a') => {
                         if let CurrentTab::Select = app.current_tab {
-                            for i in 0..app.base_columns.len() {
-                                app.specified_columns.selected_columns[i] = SelectedFlag::Selected;
-                            }
+                            app.toggle_visible_selection();
+                        } else if let CurrentTab::Where = app.current_tab {
+                            app.start_editing_constraint(true);
+                        } else if let CurrentTab::Init = app.current_tab {
+                            app.start_adding_init_setting();
+                        }
+                    },
+                    KeyCode::Char('n') if app.current_tab == CurrentTab::OrderBy => app.toggle_nulls_order(),
+                    KeyCode::Char('r') if app.current_tab == CurrentTab::GroupBy => app.start_editing_aggregate_alias(),
+                    KeyCode::Char('f') if app.current_tab == CurrentTab::Select => app.start_adding_expression(),
+                    KeyCode::Char('u') if app.current_tab == CurrentTab::Select => app.toggle_distinct(),
+                    KeyCode::Char('*') if app.current_tab == CurrentTab::Select => app.toggle_select_star(),
+                    KeyCode::Char('o') if app.current_tab == CurrentTab::Select => app.toggle_distinct_on(),
+                    KeyCode::Char('K') if app.current_tab == CurrentTab::OrderBy => app.move_order_key(true),
+                    KeyCode::Char('J') if app.current_tab == CurrentTab::OrderBy => app.move_order_key(false),
+                    KeyCode::Char('K') if app.current_tab == CurrentTab::Select => app.move_selected_column(true),
+                    KeyCode::Char('J') if app.current_tab == CurrentTab::Select => app.move_selected_column(false),
+                    KeyCode::Char('t') => {
+                        if let CurrentTab::Init = app.current_tab {
+                            app.toggle_init_kind();
+                        }
+                    },
+                    KeyCode::Char('g') => {
+                        if let CurrentTab::Where = app.current_tab {
+                            app.add_where_group();
+                        }
+                    },
+                    KeyCode::Char('d') => {
+                        if let CurrentTab::Where = app.current_tab {
+                            app.delete_where_node();
+                        } else if let CurrentTab::Select = app.current_tab {
+                            app.delete_expression();
+                        } else if let CurrentTab::Init = app.current_tab {
+                            app.delete_init_setting();
+                        } else if let CurrentTab::Values = app.current_tab {
+                            app.clear_value();
                         }
                     },
                     KeyCode::Char('e') => {
                         if let CurrentTab::Where = app.current_tab {
+                            app.start_editing_constraint(false);
+                        } else if let CurrentTab::Limit = app.current_tab {
+                            app.start_editing_limit();
+                        } else if let CurrentTab::Values = app.current_tab {
+                            app.start_editing_value();
+                        } else if app.current_tab == CurrentTab::Select && app.expression(app.current_column).is_some() {
+                            app.start_editing_expression();
+                        } else if let CurrentTab::Select = app.current_tab {
+                            app.start_editing_alias();
+                        } else if let CurrentTab::Init = app.current_tab {
+                            app.start_editing_init_value();
+                        } else if app.current_tab == CurrentTab::GroupBy {
                             app.state = AppState::Editing;
-                            app.currently_editing = Some(app::CurrentlyEditing::Constraint);
-                            if let Some(existing_constraint) = &app.specified_columns.where_constraints[app.current_column] {
-                                app.constraint_input = existing_constraint.clone();
+                            app.currently_editing = Some(app::CurrentlyEditing::Having);
+                            if let Some(existing_having) = app.having_constraint(app.current_column) {
+                                app.constraint_input = existing_having.clone();
                             }
+                        } else if app.current_tab == CurrentTab::From && app.current_column > 0 {
+                            app.state = AppState::Editing;
+                            app.currently_editing = Some(app::CurrentlyEditing::JoinCondition);
+                            app.constraint_input = app.tables[app.current_column].join_condition.clone();
                         }
                     }
                     _ => {}
                 },
+                AppState::Editing if matches!(app.currently_editing, Some(app::CurrentlyEditing::Search)) => match key.code {
+                    KeyCode::Enter => {
+                        app.state = AppState::Running;
+                        app.currently_editing = None;
+                    },
+                    KeyCode::Esc => {
+                        app.state = AppState::Running;
+                        app.currently_editing = None;
+                        app.clear_filter();
+                    },
+                    KeyCode::Down => app.next_column(),
+                    KeyCode::Up => app.previous_column(),
+                    KeyCode::Backspace => {
+                        let mut filter = app.filter.clone();
+                        filter.pop();
+                        app.set_filter(filter);
+                    },
+                    KeyCode::Char(value) => app.set_filter(format!("{}{}", app.filter, value)),
+                    _ => {}
+                },
                 AppState::Editing => {
+                    // the popups sharing the editing state only take the keys meant for them
+                    let editing = &app.currently_editing;
+                    let predicate = matches!(editing, Some(app::CurrentlyEditing::Constraint | app::CurrentlyEditing::NewConstraint));
+                    let expression = matches!(editing, Some(app::CurrentlyEditing::Expression | app::CurrentlyEditing::NewExpression));
+                    let init_setting = matches!(editing, Some(app::CurrentlyEditing::InitKey | app::CurrentlyEditing::InitValue));
                     match key.code {
-                        KeyCode::Enter => {
-                            if let Some(editing) = &app.currently_editing {
-                                app.save_constraint();
+                        KeyCode::Enter if app.currently_editing.is_some() => {
+                            match app.currently_editing {
+                                Some(app::CurrentlyEditing::InitKey) => app.save_init_key(),
+                                Some(app::CurrentlyEditing::InitValue) => app.save_init_value(),
+                                Some(app::CurrentlyEditing::Constraint | app::CurrentlyEditing::NewConstraint) => app.save_constraint(),
+                                Some(app::CurrentlyEditing::JoinCondition) => app.save_join_condition(),
+                                Some(app::CurrentlyEditing::Having) => app.save_having(),
+                                Some(app::CurrentlyEditing::SessionName) => app.save_session(),
+                                Some(app::CurrentlyEditing::Alias) => app.save_alias(),
+                                Some(app::CurrentlyEditing::AggregateAlias) => app.save_aggregate_alias(),
+                                Some(app::CurrentlyEditing::LimitValue) => app.save_limit_value(),
+                                Some(app::CurrentlyEditing::Value) => app.save_value(),
+                                Some(app::CurrentlyEditing::Expression | app::CurrentlyEditing::NewExpression) => app.save_expression(),
+                                Some(app::CurrentlyEditing::Search) | None => {}
+                            }
+                            if app.currently_editing.is_none() {
                                 app.state = AppState::Running;
                             }
                         },
                         KeyCode::Backspace => {
                             app.constraint_input.pop();
                         },
+                        KeyCode::Up if predicate => app.previous_editing_column(),
+                        KeyCode::Down if predicate => app.next_editing_column(),
+                        KeyCode::BackTab if predicate => app.previous_editing_operator(),
+                        KeyCode::Tab if predicate => app.next_editing_operator(),
+                        KeyCode::BackTab if expression => app.cycle_expression_function(false),
+                        KeyCode::Tab if expression => app.cycle_expression_function(true),
+                        KeyCode::BackTab if init_setting => app.cycle_init_input(false),
+                        KeyCode::Tab if init_setting => app.cycle_init_input(true),
                         KeyCode::Esc => {
                             app.state = AppState::Running;
                             app.clear_constraint();
                             app.currently_editing = None;
                         },
-                        KeyCode::Char(value) => {
-                            if let Some(editing) = &app.currently_editing {
-                                app.constraint_input.push(value);
-                            }
+                        KeyCode::Char(value) if app.currently_editing.is_some() => {
+                            app.constraint_input.push(value);
                         }
                         _ => {}
                     }
@@ -0,0 +1,890 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use strum::{Display, FromRepr};
+
+use crate::{dialect::Dialect, table::DataType};
+
+/// A SELECT statement, independent of the dialect it is rendered for.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    pub distinct: bool,
+    /// Keys of DISTINCT ON, they must lead the ORDER BY.
+    pub distinct_on: Vec<Expr>,
+    /// Columns to select, an empty list selects `*`.
+    pub select: Vec<SelectItem>,
+    pub from: TableRef,
+    pub joins: Vec<Join>,
+    pub where_clause: Option<Condition>,
+    pub group_by: Vec<Expr>,
+    /// HAVING predicates combined with AND.
+    pub having: Vec<Predicate>,
+    pub order_by: Vec<OrderItem>,
+    pub limit: Option<RowLimit>,
+}
+
+/// A statement changing the rows of a single table, independent of the dialect it is rendered for.
+#[derive(Debug, Clone)]
+pub enum Statement {
+    Insert { table: String, assignments: Vec<Assignment> },
+    Update { table: String, assignments: Vec<Assignment>, where_clause: Option<Condition> },
+    Delete { table: String, where_clause: Option<Condition> },
+    /// Insert the row, or update the row with the same `keys` when there is one.
+    Merge { table: String, assignments: Vec<Assignment>, keys: Vec<String> },
+}
+
+/// A column and the value written to it.
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    pub column: String,
+    pub value: SetValue,
+}
+
+/// A value written by INSERT, UPDATE or MERGE.
+#[derive(Debug, Clone)]
+pub enum SetValue {
+    Null,
+    Literal(Value),
+    /// SQL written by the user, emitted as is.
+    Raw(String),
+}
+
+/// Number of rows to return after skipping `offset` rows.
+#[derive(Debug, Clone, Copy)]
+pub struct RowLimit {
+    pub count: u64,
+    pub offset: u64,
+    /// Oracle only: filter on ROWNUM around the query, for versions before 12c
+    pub rownum: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TableRef {
+    pub name: String,
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JoinKind {
+    #[default]
+    Inner,
+    Left,
+}
+
+/// A table joined to the ones before it, an empty condition renders as a CROSS JOIN.
+#[derive(Debug, Clone)]
+pub struct Join {
+    pub kind: JoinKind,
+    pub table: TableRef,
+    pub condition: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    /// A column, qualified by the alias of its table when the query has joins.
+    Column { table: Option<String>, name: String },
+    Aggregate { function: Aggregate, arg: Box<Expr> },
+    CountAll,
+    /// An alias of the SELECT list, as used in ORDER BY.
+    Alias(String),
+    /// SQL written by the user, emitted as is.
+    Raw(String),
+}
+
+/// An expression of the SELECT list, renamed in the result when it has an alias.
+#[derive(Debug, Clone)]
+pub struct SelectItem {
+    pub expr: Expr,
+    pub alias: Option<String>,
+}
+
+impl From<Expr> for SelectItem {
+    fn from(expr: Expr) -> Self {
+        SelectItem { expr, alias: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregate {
+    Count,
+    CountDistinct,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogicalOperator {
+    #[default]
+    And,
+    Or,
+}
+
+impl LogicalOperator {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            LogicalOperator::And => "AND",
+            LogicalOperator::Or => "OR",
+        }
+    }
+}
+
+/// A boolean condition, groups nested inside another one are parenthesised.
+#[derive(Debug, Clone)]
+pub enum Condition {
+    Predicate(Predicate),
+    Group { operator: LogicalOperator, children: Vec<Condition> },
+}
+
+/// A condition on a single expression.
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    pub expr: Expr,
+    pub constraint: Constraint,
+}
+
+/// Comparison operators offered by the predicate builder, in the order they are cycled through.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, FromRepr, Display, Serialize, Deserialize)]
+pub enum Operator {
+    #[default]
+    #[strum(to_string = "=")]
+    #[serde(rename = "=")]
+    Eq,
+    #[strum(to_string = "<>")]
+    #[serde(rename = "<>")]
+    Ne,
+    #[strum(to_string = "<")]
+    #[serde(rename = "<")]
+    Lt,
+    #[strum(to_string = "<=")]
+    #[serde(rename = "<=")]
+    Le,
+    #[strum(to_string = ">")]
+    #[serde(rename = ">")]
+    Gt,
+    #[strum(to_string = ">=")]
+    #[serde(rename = ">=")]
+    Ge,
+    #[strum(to_string = "LIKE")]
+    #[serde(rename = "like")]
+    Like,
+    #[strum(to_string = "IN")]
+    #[serde(rename = "in")]
+    In,
+    #[strum(to_string = "BETWEEN")]
+    #[serde(rename = "between")]
+    Between,
+    #[strum(to_string = "IS NULL")]
+    #[serde(rename = "is null")]
+    IsNull,
+    #[strum(to_string = "IS NOT NULL")]
+    #[serde(rename = "is not null")]
+    IsNotNull,
+    /// Free text written after the expression, for anything the builder does not cover.
+    #[strum(to_string = "RAW")]
+    #[serde(rename = "raw")]
+    Raw,
+}
+
+impl Operator {
+    /// Get the previous operator, wrapping around to the last one.
+    pub fn previous(self) -> Self {
+        let current_index = self as usize;
+        let previous_index = current_index.checked_sub(1).unwrap_or(Operator::Raw as usize);
+        Self::from_repr(previous_index).unwrap_or(self)
+    }
+
+    /// Get the next operator, wrapping around to the first one.
+    pub fn next(self) -> Self {
+        let current_index = self as usize;
+        Self::from_repr(current_index + 1).unwrap_or_default()
+    }
+
+    /// Number of values the operator takes, `None` for a comma separated list.
+    pub fn arity(self) -> Option<usize> {
+        match self {
+            Operator::IsNull | Operator::IsNotNull => Some(0),
+            Operator::Between => Some(2),
+            Operator::In => None,
+            _ => Some(1),
+        }
+    }
+}
+
+/// Right-hand side of a predicate.
+#[derive(Debug, Clone)]
+pub enum Constraint {
+    Compare(Operator, Value),
+    In(Vec<Value>),
+    Between(Value, Value),
+    IsNull,
+    IsNotNull,
+    Raw(String),
+}
+
+impl Constraint {
+    /// Build a constraint from the values typed in the predicate builder, checking them
+    /// against the type of the column.
+    pub fn parse(operator: Operator, input: &str, data_type: Option<DataType>) -> Result<Constraint, String> {
+        let values: Vec<&str> = match operator {
+            Operator::Raw if input.trim().is_empty() => return Err("the SQL condition is empty".to_string()),
+            Operator::Raw => return Ok(Constraint::Raw(input.trim().to_string())),
+            Operator::IsNull | Operator::IsNotNull => Vec::new(),
+            Operator::In | Operator::Between => input.split(',').map(str::trim).collect(),
+            _ => vec![input.trim()],
+        };
+        if let Some(arity) = operator.arity() {
+            if arity > 0 && values.len() != arity {
+                return Err(format!("{} takes {} value(s) separated by commas", operator, arity));
+            }
+        }
+        if values.iter().any(|value| value.is_empty()) {
+            return Err(format!("{} is missing a value", operator));
+        }
+        let value = |i: usize| Value::parse(values[i], data_type);
+        let constraint = match operator {
+            Operator::IsNull => Constraint::IsNull,
+            Operator::IsNotNull => Constraint::IsNotNull,
+            // LIKE patterns are always strings, whatever the column type
+            Operator::Like => Constraint::Compare(operator, Value::Text(values[0].to_string())),
+            Operator::In => Constraint::In(values.iter().map(|value| Value::parse(value, data_type)).collect::<Result<_, _>>()?),
+            Operator::Between => Constraint::Between(value(0)?, value(1)?),
+            _ => Constraint::Compare(operator, value(0)?),
+        };
+        Ok(constraint)
+    }
+
+    pub fn render(&self, dialect: Dialect) -> String {
+        match self {
+            Constraint::Compare(operator, value) => format!("{} {}", operator, value.render(dialect)),
+            Constraint::In(values) => {
+                let values: Vec<String> = values.iter().map(|value| value.render(dialect)).collect();
+                format!("IN ({})", values.join(", "))
+            },
+            Constraint::Between(low, high) => format!("BETWEEN {} AND {}", low.render(dialect), high.render(dialect)),
+            Constraint::IsNull => "IS NULL".to_string(),
+            Constraint::IsNotNull => "IS NOT NULL".to_string(),
+            Constraint::Raw(text) => text.clone(),
+        }
+    }
+}
+
+/// A literal value, rendered with the quoting rules of the dialect.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Number(String),
+    Text(String),
+    Date(String),
+    Timestamp(String),
+}
+
+impl Value {
+    /// Read a value typed by the user for a column of the given type. Values of untyped columns
+    /// are text, `007` being as likely a code as a number: compare them with raw SQL to get a number.
+    pub fn parse(input: &str, data_type: Option<DataType>) -> Result<Value, String> {
+        let is_number = !input.is_empty() && input.parse::<f64>().is_ok_and(f64::is_finite);
+        match data_type {
+            Some(data_type) if data_type.is_numeric() => {
+                if is_number {
+                    Ok(Value::Number(input.to_string()))
+                } else {
+                    Err(format!("`{}` is not a number", input))
+                }
+            },
+            Some(DataType::Date) => {
+                if is_date(input) {
+                    Ok(Value::Date(input.to_string()))
+                } else {
+                    Err(format!("`{}` is not a date, expected YYYY-MM-DD", input))
+                }
+            },
+            Some(DataType::Timestamp) => {
+                if is_timestamp(input) {
+                    Ok(Value::Timestamp(input.to_string()))
+                } else if is_date(input) {
+                    Ok(Value::Timestamp(format!("{} 00:00:00", input)))
+                } else {
+                    Err(format!("`{}` is not a timestamp, expected YYYY-MM-DD HH:MI:SS", input))
+                }
+            },
+            Some(_) | None => Ok(Value::Text(input.to_string())),
+        }
+    }
+
+    fn render(&self, dialect: Dialect) -> String {
+        match self {
+            Value::Number(number) => number.clone(),
+            Value::Text(text) => dialect.string_literal(text),
+            Value::Date(date) => dialect.date_literal(date),
+            Value::Timestamp(timestamp) => dialect.timestamp_literal(timestamp),
+        }
+    }
+}
+
+/// Whether `input` is a date written as YYYY-MM-DD.
+fn is_date(input: &str) -> bool {
+    matches_pattern(input, "dddd-dd-dd")
+}
+
+/// Whether `input` is a timestamp written as YYYY-MM-DD HH:MI:SS.
+fn is_timestamp(input: &str) -> bool {
+    matches_pattern(input, "dddd-dd-dd dd:dd:dd")
+}
+
+/// Match `input` against a pattern where `d` stands for any ASCII digit.
+fn matches_pattern(input: &str, pattern: &str) -> bool {
+    input.len() == pattern.len()
+        && input
+            .chars()
+            .zip(pattern.chars())
+            .all(|(c, p)| if p == 'd' { c.is_ascii_digit() } else { c == p })
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Where NULLs are sorted, the dialect default when not given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderItem {
+    pub expr: Expr,
+    pub order: SortOrder,
+    pub nulls: Option<NullsOrder>,
+}
+
+impl Query {
+    /// Render the statement without a terminator.
+    pub fn render(&self, dialect: Dialect) -> String {
+        let mut sql = String::from("SELECT");
+        if !self.distinct_on.is_empty() {
+            let keys: Vec<String> = self.distinct_on.iter().map(|expr| expr.render(dialect)).collect();
+            sql.push_str(&format!(" DISTINCT ON ({})", keys.join(", ")));
+        } else if self.distinct {
+            sql.push_str(" DISTINCT");
+        }
+        let limit = self.limit.filter(|limit| !(dialect == Dialect::Oracle && limit.rownum));
+        if let Some(RowLimit { count, offset: 0, .. }) = limit.filter(|_| dialect == Dialect::Sqlserver) {
+            sql.push_str(&format!(" TOP {}", count));
+        }
+        if self.select.is_empty() {
+            sql.push_str(" *");
+        } else {
+            let items: Vec<String> = self.select.iter().map(|item| item.render(dialect)).collect();
+            push_clause(&mut sql, "", &items, ",");
+        }
+
+        sql.push_str(&format!("\nFROM {}", self.from.render(dialect)));
+        for join in &self.joins {
+            sql.push('\n');
+            sql.push_str(&join.render(dialect));
+        }
+
+        push_where(&mut sql, &self.where_clause, dialect);
+
+        let group_items: Vec<String> = self.group_by.iter().map(|expr| expr.render(dialect)).collect();
+        push_clause(&mut sql, "\nGROUP BY", &group_items, ",");
+
+        let having: Vec<String> = self.having.iter().map(|predicate| predicate.render(dialect)).collect();
+        push_clause(&mut sql, "\nHAVING", &having, " AND");
+
+        let mut order_items: Vec<String> = self.order_by.iter().flat_map(|item| item.render(dialect)).collect();
+        if order_items.is_empty() && dialect == Dialect::Sqlserver && limit.is_some_and(|limit| limit.offset > 0) {
+            // SQL Server only accepts OFFSET after an ORDER BY, which under DISTINCT may only sort on the output
+            order_items.push(if self.distinct { "1" } else { "(SELECT NULL)" }.to_string());
+        }
+        push_clause(&mut sql, "\nORDER BY", &order_items, ",");
+
+        match (self.limit, dialect) {
+            (Some(limit), Dialect::Oracle) if limit.rownum => return limit.wrap_with_rownum(&sql),
+            (Some(RowLimit { count, offset, .. }), Dialect::Oracle | Dialect::Sqlserver) => {
+                if offset > 0 {
+                    sql.push_str(&format!("\nOFFSET {} ROWS FETCH NEXT {} ROWS ONLY", offset, count));
+                } else if dialect == Dialect::Oracle {
+                    sql.push_str(&format!("\nFETCH FIRST {} ROWS ONLY", count));
+                }
+            },
+            (Some(RowLimit { count, offset, .. }), Dialect::Postgres | Dialect::Mysql | Dialect::Sqlite) => {
+                sql.push_str(&format!("\nLIMIT {}", count));
+                if offset > 0 {
+                    sql.push_str(&format!(" OFFSET {}", offset));
+                }
+            },
+            (None, _) => {},
+        }
+
+        sql
+    }
+}
+
+impl Statement {
+    /// Render the statement without a terminator.
+    pub fn render(&self, dialect: Dialect) -> String {
+        let columns = |assignments: &[Assignment]| -> Vec<String> {
+            assignments.iter().map(|assignment| dialect.quote_identifier(&assignment.column)).collect()
+        };
+        let values = |assignments: &[Assignment]| -> Vec<String> {
+            assignments.iter().map(|assignment| assignment.value.render(dialect)).collect()
+        };
+        match self {
+            Statement::Insert { table, assignments } => {
+                let mut sql = format!("INSERT INTO {} (", dialect.quote_identifier(table));
+                push_clause(&mut sql, "", &columns(assignments), ",");
+                sql.push_str("\n) VALUES (");
+                push_clause(&mut sql, "", &values(assignments), ",");
+                sql.push_str("\n)");
+                sql
+            },
+            Statement::Update { table, assignments, where_clause } => {
+                let mut sql = format!("UPDATE {}", dialect.quote_identifier(table));
+                let set: Vec<String> = assignments
+                    .iter()
+                    .map(|assignment| {
+                        format!("{} = {}", dialect.quote_identifier(&assignment.column), assignment.value.render(dialect))
+                    })
+                    .collect();
+                push_clause(&mut sql, "\nSET", &set, ",");
+                push_where(&mut sql, where_clause, dialect);
+                sql
+            },
+            Statement::Delete { table, where_clause } => {
+                let mut sql = format!("DELETE FROM {}", dialect.quote_identifier(table));
+                push_where(&mut sql, where_clause, dialect);
+                sql
+            },
+            Statement::Merge { table, assignments, keys } => Statement::render_merge(table, assignments, keys, dialect),
+        }
+    }
+
+    /// Render a MERGE on the dialects that have one, and an INSERT handling the key conflict on the others.
+    fn render_merge(table: &str, assignments: &[Assignment], keys: &[String], dialect: Dialect) -> String {
+        let quote = |column: &String| dialect.quote_identifier(column);
+        let updated: Vec<&String> = assignments
+            .iter()
+            .map(|assignment| &assignment.column)
+            .filter(|column| !keys.contains(column))
+            .collect();
+        match dialect {
+            Dialect::Oracle | Dialect::Sqlserver => {
+                let source: Vec<String> = assignments
+                    .iter()
+                    .map(|assignment| format!("{} AS {}", assignment.value.render(dialect), quote(&assignment.column)))
+                    .collect();
+                let on: Vec<String> = keys.iter().map(|key| format!("t.{} = s.{}", quote(key), quote(key))).collect();
+                let mut sql = format!("MERGE INTO {} t\nUSING (\n\tSELECT", dialect.quote_identifier(table));
+                sql.push_str(&source.iter().map(|item| format!("\n\t\t{}", item)).collect::<Vec<String>>().join(","));
+                if dialect == Dialect::Oracle {
+                    sql.push_str("\n\tFROM DUAL");
+                }
+                sql.push_str(&format!("\n) s\nON ({})", on.join(" AND ")));
+                // Oracle cannot update the columns of the ON clause, so a row made of keys only is left as is
+                let set: Vec<String> = updated.iter().map(|column| format!("t.{} = s.{}", quote(column), quote(column))).collect();
+                push_clause(&mut sql, "\nWHEN MATCHED THEN UPDATE SET", &set, ",");
+                let inserted: Vec<String> = assignments.iter().map(|assignment| quote(&assignment.column)).collect();
+                let values: Vec<String> = assignments.iter().map(|assignment| format!("s.{}", quote(&assignment.column))).collect();
+                sql.push_str(&format!(
+                    "\nWHEN NOT MATCHED THEN INSERT ({})\n\tVALUES ({})",
+                    inserted.join(", "),
+                    values.join(", ")
+                ));
+                sql
+            },
+            Dialect::Postgres | Dialect::Sqlite => {
+                let insert = Statement::Insert { table: table.to_string(), assignments: assignments.to_vec() };
+                let mut sql = insert.render(dialect);
+                let keys: Vec<String> = keys.iter().map(quote).collect();
+                sql.push_str(&format!("\nON CONFLICT ({})", keys.join(", ")));
+                if updated.is_empty() {
+                    sql.push_str(" DO NOTHING");
+                }
+                let set: Vec<String> = updated.iter().map(|column| format!("{} = excluded.{}", quote(column), quote(column))).collect();
+                push_clause(&mut sql, " DO UPDATE SET", &set, ",");
+                sql
+            },
+            Dialect::Mysql => {
+                let insert = Statement::Insert { table: table.to_string(), assignments: assignments.to_vec() };
+                let mut sql = insert.render(dialect);
+                // Setting a key to itself leaves an existing row untouched
+                let updated = if updated.is_empty() { vec![&keys[0]] } else { updated };
+                let set: Vec<String> = updated.iter().map(|column| format!("{} = VALUES({})", quote(column), quote(column))).collect();
+                push_clause(&mut sql, "\nON DUPLICATE KEY UPDATE", &set, ",");
+                sql
+            },
+        }
+    }
+}
+
+impl SetValue {
+    /// Read a value typed by the user: `NULL`, SQL following `=`, or a literal checked against the column type.
+    pub fn parse(input: &str, data_type: Option<DataType>) -> Result<SetValue, String> {
+        let input = input.trim();
+        if input.eq_ignore_ascii_case("null") {
+            return Ok(SetValue::Null);
+        }
+        if let Some(sql) = input.strip_prefix('=') {
+            return Ok(SetValue::Raw(sql.trim().to_string()));
+        }
+        Value::parse(input, data_type).map(SetValue::Literal)
+    }
+
+    /// Condition matching the rows holding this value.
+    pub fn constraint(&self) -> Constraint {
+        match self {
+            SetValue::Null => Constraint::IsNull,
+            SetValue::Literal(value) => Constraint::Compare(Operator::Eq, value.clone()),
+            SetValue::Raw(sql) => Constraint::Raw(format!("= {}", sql)),
+        }
+    }
+
+    fn render(&self, dialect: Dialect) -> String {
+        match self {
+            SetValue::Null => "NULL".to_string(),
+            SetValue::Literal(value) => value.render(dialect),
+            SetValue::Raw(sql) => sql.clone(),
+        }
+    }
+}
+
+impl RowLimit {
+    /// Limit the rows of `sql` with ROWNUM, numbering them in a second subquery when rows are skipped.
+    fn wrap_with_rownum(&self, sql: &str) -> String {
+        let indent = |sql: &str, depth: usize| {
+            sql.lines().map(|line| format!("{}{}", "\t".repeat(depth), line)).collect::<Vec<String>>().join("\n")
+        };
+        if self.offset == 0 {
+            return format!("SELECT *\nFROM (\n{}\n)\nWHERE ROWNUM <= {}", indent(sql, 1), self.count);
+        }
+        format!(
+            "SELECT *\nFROM (\n\tSELECT q.*, ROWNUM AS rn\n\tFROM (\n{}\n\t) q\n\tWHERE ROWNUM <= {}\n)\nWHERE rn > {}",
+            indent(sql, 2),
+            self.offset + self.count,
+            self.offset
+        )
+    }
+}
+
+impl JoinKind {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            JoinKind::Inner => "INNER JOIN",
+            JoinKind::Left => "LEFT JOIN",
+        }
+    }
+}
+
+impl TableRef {
+    fn render(&self, dialect: Dialect) -> String {
+        match &self.alias {
+            Some(alias) => format!("{} {}", dialect.quote_identifier(&self.name), dialect.quote_identifier(alias)),
+            None => dialect.quote_identifier(&self.name),
+        }
+    }
+}
+
+impl Join {
+    fn render(&self, dialect: Dialect) -> String {
+        let condition = self.condition.trim();
+        if condition.is_empty() {
+            return format!("CROSS JOIN {}", self.table.render(dialect));
+        }
+        format!("{} {} ON {}", self.kind.keyword(), self.table.render(dialect), condition)
+    }
+}
+
+impl Expr {
+    pub fn render(&self, dialect: Dialect) -> String {
+        match self {
+            Expr::Column { table: Some(table), name } => {
+                format!("{}.{}", dialect.quote_identifier(table), dialect.quote_identifier(name))
+            },
+            Expr::Column { table: None, name } => dialect.quote_identifier(name),
+            Expr::Aggregate { function: Aggregate::CountDistinct, arg } => format!("COUNT(DISTINCT {})", arg.render(dialect)),
+            Expr::Aggregate { function, arg } => format!("{}({})", function, arg.render(dialect)),
+            Expr::CountAll => "COUNT(*)".to_string(),
+            Expr::Alias(alias) => dialect.quote_identifier(alias),
+            Expr::Raw(sql) => sql.clone(),
+        }
+    }
+}
+
+impl SelectItem {
+    fn render(&self, dialect: Dialect) -> String {
+        match &self.alias {
+            Some(alias) => format!("{} AS {}", self.expr.render(dialect), dialect.quote_identifier(alias)),
+            None => self.expr.render(dialect),
+        }
+    }
+}
+
+impl fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Aggregate::Count => "COUNT",
+            Aggregate::CountDistinct => "COUNT DISTINCT",
+            Aggregate::Sum => "SUM",
+            Aggregate::Avg => "AVG",
+            Aggregate::Min => "MIN",
+            Aggregate::Max => "MAX",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Condition {
+    fn render(&self, dialect: Dialect) -> String {
+        match self {
+            Condition::Predicate(predicate) => predicate.render(dialect),
+            Condition::Group { operator, children } => {
+                let conditions: Vec<String> = children.iter().map(|condition| condition.render(dialect)).collect();
+                format!("({})", conditions.join(&format!(" {} ", operator.keyword())))
+            },
+        }
+    }
+}
+
+impl Predicate {
+    fn render(&self, dialect: Dialect) -> String {
+        format!("{} {}", self.expr.render(dialect), self.constraint.render(dialect))
+    }
+}
+
+impl OrderItem {
+    /// Render the sort key, preceded by a key sorting the NULLs when the dialect has no NULLS FIRST/LAST.
+    fn render(&self, dialect: Dialect) -> Vec<String> {
+        let expr = self.expr.render(dialect);
+        let order = match self.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+        match self.nulls {
+            None => vec![format!("{} {}", expr, order)],
+            Some(nulls) if dialect.supports_nulls_order() => {
+                let nulls = match nulls {
+                    NullsOrder::First => "NULLS FIRST",
+                    NullsOrder::Last => "NULLS LAST",
+                };
+                vec![format!("{} {} {}", expr, order, nulls)]
+            },
+            Some(nulls) => {
+                let (null_rank, value_rank) = match nulls {
+                    NullsOrder::First => (0, 1),
+                    NullsOrder::Last => (1, 0),
+                };
+                vec![
+                    format!("CASE WHEN {} IS NULL THEN {} ELSE {} END", expr, null_rank, value_rank),
+                    format!("{} {}", expr, order),
+                ]
+            },
+        }
+    }
+}
+
+/// Append the WHERE clause, the conditions of a top-level group going one per line.
+fn push_where(sql: &mut String, where_clause: &Option<Condition>, dialect: Dialect) {
+    match where_clause {
+        Some(Condition::Group { operator, children }) => {
+            let conditions: Vec<String> = children.iter().map(|condition| condition.render(dialect)).collect();
+            push_clause(sql, "\nWHERE", &conditions, &format!(" {}", operator.keyword()));
+        },
+        Some(condition) => push_clause(sql, "\nWHERE", &[condition.render(dialect)], ""),
+        None => {},
+    }
+}
+
+/// Append `keyword` followed by one indented item per line, or nothing if there are no items.
+fn push_clause(sql: &mut String, keyword: &str, items: &[String], separator: &str) {
+    if items.is_empty() {
+        return;
+    }
+    sql.push_str(keyword);
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            sql.push_str(separator);
+        }
+        sql.push_str("\n\t");
+        sql.push_str(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str) -> Expr {
+        Expr::Column { table: None, name: name.to_string() }
+    }
+
+    fn query(limit: Option<RowLimit>) -> Query {
+        Query {
+            select: vec![column("LAST_NAME").into()],
+            from: TableRef { name: "EMPLOYEES".to_string(), alias: None },
+            limit,
+            ..Query::default()
+        }
+    }
+
+    fn sorted(order_by: Vec<OrderItem>) -> Query {
+        Query { from: TableRef { name: "EMPLOYEES".to_string(), alias: None }, order_by, ..Query::default() }
+    }
+
+    fn assignment(column: &str, value: SetValue) -> Assignment {
+        Assignment { column: column.to_string(), value }
+    }
+
+    #[test]
+    fn limits_rows_per_dialect() {
+        let limited = query(Some(RowLimit { count: 10, offset: 0, rownum: false }));
+        assert_eq!(limited.render(Dialect::Oracle), "SELECT\n\tLAST_NAME\nFROM EMPLOYEES\nFETCH FIRST 10 ROWS ONLY");
+        assert_eq!(limited.render(Dialect::Sqlserver), "SELECT TOP 10\n\tLAST_NAME\nFROM EMPLOYEES");
+        for dialect in [Dialect::Postgres, Dialect::Mysql, Dialect::Sqlite] {
+            assert_eq!(limited.render(dialect), "SELECT\n\tLAST_NAME\nFROM EMPLOYEES\nLIMIT 10");
+        }
+    }
+
+    #[test]
+    fn skips_rows_per_dialect() {
+        let page = query(Some(RowLimit { count: 10, offset: 20, rownum: false }));
+        assert_eq!(
+            page.render(Dialect::Oracle),
+            "SELECT\n\tLAST_NAME\nFROM EMPLOYEES\nOFFSET 20 ROWS FETCH NEXT 10 ROWS ONLY"
+        );
+        // SQL Server needs an ORDER BY before OFFSET
+        assert_eq!(
+            page.render(Dialect::Sqlserver),
+            "SELECT\n\tLAST_NAME\nFROM EMPLOYEES\nORDER BY\n\t(SELECT NULL)\nOFFSET 20 ROWS FETCH NEXT 10 ROWS ONLY"
+        );
+        let distinct_page = Query { distinct: true, ..query(Some(RowLimit { count: 10, offset: 20, rownum: false })) };
+        assert_eq!(
+            distinct_page.render(Dialect::Sqlserver),
+            "SELECT DISTINCT\n\tLAST_NAME\nFROM EMPLOYEES\nORDER BY\n\t1\nOFFSET 20 ROWS FETCH NEXT 10 ROWS ONLY"
+        );
+        for dialect in [Dialect::Postgres, Dialect::Mysql, Dialect::Sqlite] {
+            assert_eq!(page.render(dialect), "SELECT\n\tLAST_NAME\nFROM EMPLOYEES\nLIMIT 10 OFFSET 20");
+        }
+    }
+
+    #[test]
+    fn limits_rows_with_rownum_on_oracle_only() {
+        let first = query(Some(RowLimit { count: 10, offset: 0, rownum: true }));
+        assert_eq!(
+            first.render(Dialect::Oracle),
+            "SELECT *\nFROM (\n\tSELECT\n\t\tLAST_NAME\n\tFROM EMPLOYEES\n)\nWHERE ROWNUM <= 10"
+        );
+        let page = query(Some(RowLimit { count: 10, offset: 20, rownum: true }));
+        assert_eq!(
+            page.render(Dialect::Oracle),
+            "SELECT *\nFROM (\n\tSELECT q.*, ROWNUM AS rn\n\tFROM (\n\t\tSELECT\n\t\t\tLAST_NAME\n\t\tFROM EMPLOYEES\n\t) q\n\tWHERE ROWNUM <= 30\n)\nWHERE rn > 20"
+        );
+        assert_eq!(page.render(Dialect::Postgres), "SELECT\n\tLAST_NAME\nFROM EMPLOYEES\nLIMIT 10 OFFSET 20");
+    }
+
+    #[test]
+    fn sorts_nulls_per_dialect() {
+        let query = sorted(vec![OrderItem { expr: column("SALARY"), order: SortOrder::Desc, nulls: Some(NullsOrder::Last) }]);
+        for dialect in [Dialect::Oracle, Dialect::Postgres, Dialect::Sqlite] {
+            assert!(query.render(dialect).ends_with("ORDER BY\n\tSALARY DESC NULLS LAST"), "{}", dialect);
+        }
+        // The dialects without NULLS FIRST/LAST sort on whether the key is NULL first
+        for dialect in [Dialect::Mysql, Dialect::Sqlserver] {
+            assert!(
+                query.render(dialect).ends_with("ORDER BY\n\tCASE WHEN SALARY IS NULL THEN 1 ELSE 0 END,\n\tSALARY DESC"),
+                "{}",
+                dialect
+            );
+        }
+        let query = sorted(vec![OrderItem { expr: column("SALARY"), order: SortOrder::Asc, nulls: Some(NullsOrder::First) }]);
+        assert!(query.render(Dialect::Mysql).ends_with("CASE WHEN SALARY IS NULL THEN 0 ELSE 1 END,\n\tSALARY ASC"));
+    }
+
+    #[test]
+    fn merges_or_upserts_per_dialect() {
+        let merge = Statement::Merge {
+            table: "EMPLOYEES".to_string(),
+            assignments: vec![
+                assignment("EMPLOYEE_ID", SetValue::Literal(Value::Number("100".to_string()))),
+                assignment("LAST_NAME", SetValue::Literal(Value::Text("King".to_string()))),
+            ],
+            keys: vec!["EMPLOYEE_ID".to_string()],
+        };
+        assert_eq!(
+            merge.render(Dialect::Oracle),
+            "MERGE INTO EMPLOYEES t\nUSING (\n\tSELECT\n\t\t100 AS EMPLOYEE_ID,\n\t\t'King' AS LAST_NAME\n\tFROM DUAL\n) s\n\
+             ON (t.EMPLOYEE_ID = s.EMPLOYEE_ID)\nWHEN MATCHED THEN UPDATE SET\n\tt.LAST_NAME = s.LAST_NAME\n\
+             WHEN NOT MATCHED THEN INSERT (EMPLOYEE_ID, LAST_NAME)\n\tVALUES (s.EMPLOYEE_ID, s.LAST_NAME)"
+        );
+        assert!(!merge.render(Dialect::Sqlserver).contains("DUAL"));
+        let insert = "INSERT INTO EMPLOYEES (\n\tEMPLOYEE_ID,\n\tLAST_NAME\n) VALUES (\n\t100,\n\t'King'\n)";
+        for dialect in [Dialect::Postgres, Dialect::Sqlite] {
+            assert_eq!(
+                merge.render(dialect),
+                format!("{}\nON CONFLICT (EMPLOYEE_ID) DO UPDATE SET\n\tLAST_NAME = excluded.LAST_NAME", insert)
+            );
+        }
+        assert_eq!(
+            merge.render(Dialect::Mysql),
+            format!("{}\nON DUPLICATE KEY UPDATE\n\tLAST_NAME = VALUES(LAST_NAME)", insert)
+        );
+    }
+
+    #[test]
+    fn merges_keys_only() {
+        let merge = Statement::Merge {
+            table: "EMPLOYEES".to_string(),
+            assignments: vec![assignment("EMPLOYEE_ID", SetValue::Literal(Value::Number("100".to_string())))],
+            keys: vec!["EMPLOYEE_ID".to_string()],
+        };
+        assert!(!merge.render(Dialect::Oracle).contains("WHEN MATCHED"));
+        assert!(merge.render(Dialect::Postgres).ends_with("ON CONFLICT (EMPLOYEE_ID) DO NOTHING"));
+        assert!(merge.render(Dialect::Mysql).ends_with("ON DUPLICATE KEY UPDATE\n\tEMPLOYEE_ID = VALUES(EMPLOYEE_ID)"));
+    }
+
+    #[test]
+    fn parses_constraints() {
+        let number = Some(DataType::Integer);
+        assert_eq!(Constraint::parse(Operator::Between, "1, 5", number).unwrap().render(Dialect::Oracle), "BETWEEN 1 AND 5");
+        assert!(Constraint::parse(Operator::Between, "1", number).is_err());
+        assert!(Constraint::parse(Operator::Eq, "abc", number).is_err());
+        assert!(Constraint::parse(Operator::Eq, " ", number).is_err());
+        assert!(Constraint::parse(Operator::In, "1,,2", number).is_err());
+        assert!(Constraint::parse(Operator::Raw, "", None).is_err());
+        assert_eq!(Constraint::parse(Operator::IsNull, "", number).unwrap().render(Dialect::Oracle), "IS NULL");
+    }
+
+    #[test]
+    fn renders_literals_per_dialect() {
+        let constraint = Constraint::parse(Operator::Between, "2024-01-01, 2024-01-31 12:00:00", Some(DataType::Timestamp)).unwrap();
+        assert_eq!(
+            constraint.render(Dialect::Oracle),
+            "BETWEEN TIMESTAMP '2024-01-01 00:00:00' AND TIMESTAMP '2024-01-31 12:00:00'"
+        );
+        assert_eq!(
+            constraint.render(Dialect::Sqlserver),
+            "BETWEEN '2024-01-01T00:00:00' AND '2024-01-31T12:00:00'"
+        );
+        let constraint = Constraint::parse(Operator::Eq, "2024-01-01", Some(DataType::Date)).unwrap();
+        assert_eq!(constraint.render(Dialect::Mysql), "= DATE '2024-01-01'");
+        assert_eq!(constraint.render(Dialect::Sqlite), "= '2024-01-01'");
+        let constraint = Constraint::parse(Operator::Eq, "O'Neil", Some(DataType::Varchar2(None))).unwrap();
+        assert_eq!(constraint.render(Dialect::Postgres), "= 'O''Neil'");
+    }
+
+    #[test]
+    fn quotes_values_of_untyped_columns() {
+        let constraint = Constraint::parse(Operator::In, "007, 42", None).unwrap();
+        assert_eq!(constraint.render(Dialect::Postgres), "IN ('007', '42')");
+        let constraint = Constraint::parse(Operator::Eq, "42", Some(DataType::Number { precision: None, scale: None })).unwrap();
+        assert_eq!(constraint.render(Dialect::Postgres), "= 42");
+    }
+}
@@ -0,0 +1,769 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    de::StringOrMap,
+    app::{App, ConditionNode, GroupFlag, OrderKey, Pagination, SelectStar, SelectedColumn, StatementKind},
+    dialect::Dialect,
+    init::{self, SettingSpec},
+    query::{Aggregate, Constraint, JoinKind, LogicalOperator, NullsOrder, Operator, SortOrder},
+};
+
+/// A query described in a file, to generate SQL without the interactive editor.
+///
+/// Columns are referred to by name, qualified by the table alias or name when
+/// several tables are joined:
+///
+/// ```toml
+/// tables = ["EMPLOYEES", "DEPARTMENTS"]
+/// select = ["e.EMPLOYEE_ID", "LAST_NAME", { column = "DEPARTMENT_NAME", alias = "DEPARTMENT" }]
+/// distinct = true
+/// order_by = [{ column = "LAST_NAME", order = "desc", nulls = "last" }, "FIRST_NAME"]
+/// expressions = [{ name = "HIRED", sql = "TO_CHAR(e.HIRE_DATE, 'YYYY-MM')" }]
+/// limit = { rows = 100, offset = 200 }
+/// statement = "update"
+/// values = { SALARY = "5000", UPDATED_AT = "=CURRENT_TIMESTAMP" }
+///
+/// [where]
+/// all = [
+///     { column = "SALARY", op = ">=", value = "1000" },
+///     { any = [{ column = "d.LOCATION_ID", op = "in", value = "1, 2" }, { column = "COMMISSION_PCT", op = "is null" }] },
+/// ]
+/// ```
+#[derive(Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuerySpec {
+    /// Tables of the tables directory, the first one is the FROM table and the others are joined
+    pub tables: Vec<String>,
+    /// Table definition files read outside the tables directory, joined after `tables`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub table_files: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<Dialect>,
+    /// INIT settings, the default settings of the dialect are used when left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init: Option<Vec<SettingSpec>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub joins: Vec<JoinSpec>,
+    /// Expression columns, referred to by their name in the other sections
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expressions: Vec<ExpressionSpec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub select: Vec<SelectSpec>,
+    /// When to write `select` as `*`, overridden by --select-star
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub select_star: Option<SelectStar>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub distinct: bool,
+    /// Columns of `select` to keep one row for (PostgreSQL), implies `distinct`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub distinct_on: Vec<String>,
+    #[serde(default, rename = "where", skip_serializing_if = "Option::is_none")]
+    pub where_clause: Option<ConditionSpec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_by: Vec<GroupSpec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aggregates: Vec<AggregateSpec>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order_by: Vec<OrderSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<LimitSpec>,
+    /// Statement to generate instead of a SELECT, on the FROM table
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statement: Option<StatementKind>,
+    /// Values written by INSERT, UPDATE and MERGE: a literal, `NULL` or SQL following `=`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<String, String>,
+}
+
+/// How a joined table is joined, the inferred condition is kept when `on` is left out.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JoinSpec {
+    pub table: String,
+    #[serde(default)]
+    pub kind: JoinKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on: Option<String>,
+}
+
+/// An expression column, selected unless `select` lists the columns or `selected` is false.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpressionSpec {
+    pub name: String,
+    pub sql: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected: Option<bool>,
+}
+
+/// A column of the SELECT list, either a column reference or a column with an alias.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged, from = "StringOrMap<AliasedSpec>")]
+pub enum SelectSpec {
+    Column(String),
+    Aliased(AliasedSpec),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AliasedSpec {
+    pub column: String,
+    pub alias: String,
+}
+
+impl From<StringOrMap<AliasedSpec>> for SelectSpec {
+    fn from(select: StringOrMap<AliasedSpec>) -> Self {
+        match select {
+            StringOrMap::String(column) => SelectSpec::Column(column),
+            StringOrMap::Map(aliased) => SelectSpec::Aliased(aliased),
+        }
+    }
+}
+
+/// A node of the WHERE tree, `value` is written as in the predicate builder.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged, try_from = "ConditionFields")]
+pub enum ConditionSpec {
+    All { all: Vec<ConditionSpec> },
+    Any { any: Vec<ConditionSpec> },
+    Predicate {
+        column: String,
+        op: Operator,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        value: String,
+    },
+}
+
+/// The keys of every kind of WHERE node, read together so that a misspelled key is named.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConditionFields {
+    all: Option<Vec<ConditionSpec>>,
+    any: Option<Vec<ConditionSpec>>,
+    column: Option<String>,
+    op: Option<Operator>,
+    #[serde(default)]
+    value: String,
+}
+
+impl TryFrom<ConditionFields> for ConditionSpec {
+    type Error = String;
+
+    fn try_from(fields: ConditionFields) -> Result<Self, Self::Error> {
+        match fields {
+            ConditionFields { all: Some(all), any: None, column: None, op: None, .. } => Ok(ConditionSpec::All { all }),
+            ConditionFields { all: None, any: Some(any), column: None, op: None, .. } => Ok(ConditionSpec::Any { any }),
+            ConditionFields { all: None, any: None, column: Some(column), op: Some(op), value } => {
+                Ok(ConditionSpec::Predicate { column, op, value })
+            },
+            _ => Err("a condition is either `all`, `any`, or a `column` with its `op` and `value`".to_string()),
+        }
+    }
+}
+
+/// A GROUP BY key, either a column reference or a column with a HAVING constraint.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged, from = "StringOrMap<HavingSpec>")]
+pub enum GroupSpec {
+    Column(String),
+    Having(HavingSpec),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HavingSpec {
+    pub column: String,
+    pub having: Option<String>,
+}
+
+impl From<StringOrMap<HavingSpec>> for GroupSpec {
+    fn from(group: StringOrMap<HavingSpec>) -> Self {
+        match group {
+            StringOrMap::String(column) => GroupSpec::Column(column),
+            StringOrMap::Map(having) => GroupSpec::Having(having),
+        }
+    }
+}
+
+/// An aggregate of the GROUP BY tab, COUNT(*) when `column` is left out.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AggregateSpec {
+    pub function: Aggregate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub having: Option<String>,
+}
+
+/// An ORDER BY item, either a column reference sorted ascending or a column with its order,
+/// the items are listed in sort priority.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged, from = "StringOrMap<OrderedSpec>")]
+pub enum OrderSpec {
+    Column(String),
+    Ordered(OrderedSpec),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OrderedSpec {
+    pub column: String,
+    #[serde(default)]
+    pub order: SortOrder,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nulls: Option<NullsOrder>,
+}
+
+impl From<StringOrMap<OrderedSpec>> for OrderSpec {
+    fn from(order: StringOrMap<OrderedSpec>) -> Self {
+        match order {
+            StringOrMap::String(column) => OrderSpec::Column(column),
+            StringOrMap::Map(ordered) => OrderSpec::Ordered(ordered),
+        }
+    }
+}
+
+/// Row limiting, `keyset` starts after the parameters holding the ORDER BY keys of the last row seen.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LimitSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows: Option<u64>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offset: u64,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub rownum: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub keyset: bool,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl QuerySpec {
+    /// Load a spec from a JSON file, or a TOML file for any other extension.
+    pub fn load(path: &Path) -> Result<QuerySpec, String> {
+        let str = fs::read_to_string(path).map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+        let spec = if path.extension().is_some_and(|extension| extension == "json") {
+            serde_json::from_str(&str).map_err(|err| err.to_string())
+        } else {
+            toml::from_str(&str).map_err(|err| err.to_string())
+        };
+        spec.map_err(|err| format!("invalid query spec {}: {}", path.display(), err))
+    }
+
+    /// Save the spec as TOML, creating the directory if needed.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let str = toml::to_string(self)?;
+        fs::write(path, str).map_err(|err| format!("could not write {}: {}", path.display(), err))?;
+        Ok(())
+    }
+
+    /// Describe the current selections of the app.
+    pub fn from_app(app: &App) -> QuerySpec {
+        let columns = &app.specified_columns;
+        let mut spec = QuerySpec {
+            tables: app.tables.iter().filter(|source| source.file.is_none()).map(|source| source.name.clone()).collect(),
+            table_files: app.tables.iter().filter_map(|source| source.file.clone()).collect(),
+            dialect: Some(app.dialect),
+            init: Some(
+                app.init_config
+                    .iter()
+                    .map(|setting| SettingSpec {
+                        kind: Some(setting.kind),
+                        key: setting.key.clone(),
+                        value: setting.value.clone(),
+                        enabled: setting.enabled,
+                    })
+                    .collect(),
+            ),
+            joins: app.tables[1.min(app.tables.len())..]
+                .iter()
+                .map(|source| JoinSpec {
+                    table: source.name.clone(),
+                    kind: source.join_kind,
+                    on: Some(source.join_condition.clone()),
+                })
+                .collect(),
+            expressions: app
+                .expressions
+                .iter()
+                .enumerate()
+                .map(|(index, sql)| {
+                    let i = app.column_tables.len() + index;
+                    ExpressionSpec {
+                        name: app.base_columns[i].name.clone(),
+                        sql: sql.clone(),
+                        selected: app.selected_column(i).is_none().then_some(false),
+                    }
+                })
+                .collect(),
+            select_star: Some(app.select_star).filter(|select_star| *select_star != SelectStar::Auto),
+            distinct: app.distinct,
+            where_clause: condition_spec(app, &columns.where_clause),
+            limit: (app.pagination != Pagination::default()).then_some(LimitSpec {
+                rows: app.pagination.rows,
+                offset: app.pagination.offset,
+                rownum: app.pagination.rownum,
+                keyset: app.pagination.keyset,
+            }),
+            statement: Some(app.statement).filter(|statement| *statement != StatementKind::Select),
+            ..QuerySpec::default()
+        };
+        for (i, value) in columns.values.iter().enumerate() {
+            if let Some(value) = value {
+                spec.values.insert(app.column_label(i), value.clone());
+            }
+        }
+
+        for selected in &columns.selected_columns {
+            let column = app.column_label(selected.column);
+            if selected.distinct_on {
+                spec.distinct_on.push(column.clone());
+            }
+            spec.select.push(match &selected.alias {
+                Some(alias) => SelectSpec::Aliased(AliasedSpec { column, alias: alias.clone() }),
+                None => SelectSpec::Column(column),
+            });
+        }
+        for i in 0..app.base_columns.len() {
+            let column = app.column_label(i);
+            let having = columns.having_constraints[i].clone();
+            match columns.grouped_columns[i] {
+                GroupFlag::Off => {},
+                GroupFlag::Key if having.is_none() => spec.group_by.push(GroupSpec::Column(column.clone())),
+                GroupFlag::Key => spec.group_by.push(GroupSpec::Having(HavingSpec { column: column.clone(), having })),
+                GroupFlag::Aggregate(function) => spec.aggregates.push(AggregateSpec {
+                    function,
+                    column: Some(column.clone()),
+                    alias: columns.aggregate_aliases[i].clone(),
+                    having,
+                }),
+            }
+        }
+        for key in &columns.ordered_columns {
+            let column = app.column_label(key.column);
+            spec.order_by.push(match key {
+                OrderKey { order: SortOrder::Asc, nulls: None, .. } => OrderSpec::Column(column),
+                OrderKey { order, nulls, .. } => OrderSpec::Ordered(OrderedSpec { column, order: *order, nulls: *nulls }),
+            });
+        }
+        if app.count_all {
+            spec.aggregates.push(AggregateSpec {
+                function: Aggregate::Count,
+                column: None,
+                alias: app.count_all_alias.clone(),
+                having: app.count_all_having.clone(),
+            });
+        }
+        spec
+    }
+
+    /// Apply the spec to an app built from its tables, returning what could not be applied.
+    pub fn apply(&self, app: &mut App) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        for join in &self.joins {
+            let found = app.tables.iter().position(|source| {
+                source.name.eq_ignore_ascii_case(&join.table) || source.alias.eq_ignore_ascii_case(&join.table)
+            });
+            match found {
+                Some(0) => errors.push(format!("joins: `{}` is the FROM table", join.table)),
+                Some(i) => {
+                    app.tables[i].join_kind = join.kind;
+                    if let Some(on) = &join.on {
+                        app.tables[i].join_condition = on.trim().to_string();
+                    }
+                },
+                None => errors.push(format!("joins: unknown table `{}`", join.table)),
+            }
+        }
+
+        for expression in &self.expressions {
+            let (name, sql) = (expression.name.trim(), expression.sql.trim());
+            match app.validate_expression(None, name, sql) {
+                Ok(()) => {
+                    app.add_expression(name.to_string(), sql.to_string());
+                    if expression.selected == Some(false) {
+                        let i = app.base_columns.len() - 1;
+                        app.specified_columns.selected_columns.retain(|selected| selected.column != i);
+                    }
+                },
+                Err(err) => errors.push(format!("expressions: `{}`: {}", name, err)),
+            }
+        }
+        if !self.select.is_empty() {
+            // the SELECT list is the one given, expression columns included
+            app.specified_columns.selected_columns.clear();
+        }
+
+        for select in &self.select {
+            let (column, alias) = match select {
+                SelectSpec::Column(column) => (column, None),
+                SelectSpec::Aliased(AliasedSpec { column, alias }) => (column, Some(alias.trim().to_string())),
+            };
+            let Some(i) = find_column(app, "select", column, &mut errors) else {
+                continue;
+            };
+            if let Some(Err(err)) = alias.as_ref().map(|alias| app.validate_alias(i, alias)) {
+                errors.push(format!("select: `{}`: {}", column, err));
+                continue;
+            }
+            let selected_columns = &mut app.specified_columns.selected_columns;
+            selected_columns.retain(|selected| selected.column != i);
+            selected_columns.push(SelectedColumn { column: i, alias, distinct_on: false });
+        }
+
+        if let Some(select_star) = self.select_star {
+            app.select_star = select_star;
+        }
+        app.distinct = self.distinct || !self.distinct_on.is_empty();
+        if !self.distinct_on.is_empty() && !app.dialect.supports_distinct_on() {
+            errors.push(format!("distinct_on: DISTINCT ON is not supported by {}", app.dialect));
+        }
+        for column in &self.distinct_on {
+            let Some(i) = find_column(app, "distinct_on", column, &mut errors) else {
+                continue;
+            };
+            match app.specified_columns.selected_columns.iter_mut().find(|selected| selected.column == i) {
+                Some(selected) => selected.distinct_on = true,
+                None => errors.push(format!("distinct_on: `{}` is not in `select`", column)),
+            }
+        }
+
+        if let Some(condition) = &self.where_clause {
+            app.specified_columns.where_clause = match condition_node(app, condition, &mut errors) {
+                Some(predicate @ ConditionNode::Predicate { .. }) => {
+                    ConditionNode::Group { operator: LogicalOperator::And, children: vec![predicate] }
+                },
+                Some(group) => group,
+                None => ConditionNode::default(),
+            };
+        }
+
+        for group in &self.group_by {
+            let (column, having) = match group {
+                GroupSpec::Column(column) => (column, &None),
+                GroupSpec::Having(HavingSpec { column, having }) => (column, having),
+            };
+            if let Some(i) = find_column(app, "group_by", column, &mut errors) {
+                app.specified_columns.grouped_columns[i] = GroupFlag::Key;
+                app.specified_columns.having_constraints[i] = having.clone();
+            }
+        }
+
+        for aggregate in &self.aggregates {
+            let i = match (&aggregate.column, aggregate.function) {
+                (None, Aggregate::Count) => {
+                    app.count_all = true;
+                    app.count_all_having = aggregate.having.clone();
+                    app.base_columns.len()
+                },
+                (None, function) => {
+                    errors.push(format!("aggregates: {} needs a column", function));
+                    continue;
+                },
+                (Some(column), function) => {
+                    let Some(i) = find_column(app, "aggregates", column, &mut errors) else {
+                        continue;
+                    };
+                    app.specified_columns.grouped_columns[i] = GroupFlag::Aggregate(function);
+                    app.specified_columns.having_constraints[i] = aggregate.having.clone();
+                    i
+                },
+            };
+            let Some(alias) = aggregate.alias.as_ref().map(|alias| alias.trim().to_string()) else {
+                continue;
+            };
+            match app.validate_alias(i, &alias) {
+                Ok(()) if i == app.base_columns.len() => app.count_all_alias = Some(alias),
+                Ok(()) => app.specified_columns.aggregate_aliases[i] = Some(alias),
+                Err(err) => errors.push(format!("aggregates: `{}`: {}", app.group_label(i), err)),
+            }
+        }
+
+        for order in &self.order_by {
+            let (column, order, nulls) = match order {
+                OrderSpec::Column(column) => (column, SortOrder::Asc, None),
+                OrderSpec::Ordered(OrderedSpec { column, order, nulls }) => (column, *order, *nulls),
+            };
+            if let Some(i) = find_column(app, "order_by", column, &mut errors) {
+                let ordered_columns = &mut app.specified_columns.ordered_columns;
+                ordered_columns.retain(|key| key.column != i);
+                ordered_columns.push(OrderKey { column: i, order, nulls });
+            }
+        }
+
+        if let Some(statement) = self.statement {
+            app.statement = statement;
+        }
+        for (column, value) in &self.values {
+            let Some(i) = find_column(app, "values", column, &mut errors) else {
+                continue;
+            };
+            if app.expression(i).is_some() {
+                errors.push(format!("values: `{}` is an expression column", column));
+                continue;
+            }
+            match app.validate_value(i, value) {
+                Ok(()) => app.specified_columns.values[i] = Some(value.trim().to_string()),
+                Err(err) => errors.push(format!("values: `{}`: {}", column, err)),
+            }
+        }
+
+        if let Some(limit) = &self.limit {
+            if limit.rows == Some(0) {
+                errors.push("limit: rows must be at least 1".to_string());
+            }
+            app.pagination = Pagination {
+                rows: limit.rows.filter(|rows| *rows > 0),
+                offset: limit.offset,
+                rownum: limit.rownum,
+                keyset: limit.keyset,
+            };
+        }
+
+        errors
+    }
+}
+
+/// File of a saved session, `<config dir>/sessions/<name>.toml`,
+/// unless `name` is already a path to a spec file.
+pub fn session_path(name: &str) -> Result<PathBuf, String> {
+    let path = Path::new(name);
+    if path.extension().is_some() || path.components().count() > 1 {
+        return Ok(path.to_path_buf());
+    }
+    let dir = init::config_dir().ok_or("no configuration directory, set HOME or XDG_CONFIG_HOME")?;
+    Ok(dir.join("sessions").join(format!("{}.toml", name)))
+}
+
+fn find_column(app: &App, section: &str, reference: &str, errors: &mut Vec<String>) -> Option<usize> {
+    let found = app.find_column(reference);
+    if found.is_none() {
+        errors.push(format!("{}: unknown column `{}`", section, reference));
+    }
+    found
+}
+
+/// Build the WHERE tree, leaving out predicates on unknown columns.
+fn condition_node(app: &App, condition: &ConditionSpec, errors: &mut Vec<String>) -> Option<ConditionNode> {
+    let (operator, children) = match condition {
+        ConditionSpec::All { all } => (LogicalOperator::And, all),
+        ConditionSpec::Any { any } => (LogicalOperator::Or, any),
+        ConditionSpec::Predicate { column, op, value } => {
+            let i = find_column(app, "where", column, errors)?;
+            let input = value.trim().to_string();
+            if let Err(err) = Constraint::parse(*op, &input, app.base_columns[i].data_type) {
+                errors.push(format!("where: `{}`: {}", column, err));
+            }
+            return Some(ConditionNode::Predicate { column: i, operator: *op, input });
+        },
+    };
+    Some(ConditionNode::Group {
+        operator,
+        children: children.iter().filter_map(|child| condition_node(app, child, errors)).collect(),
+    })
+}
+
+/// Describe a node of the WHERE tree, `None` for an empty group.
+fn condition_spec(app: &App, node: &ConditionNode) -> Option<ConditionSpec> {
+    match node {
+        ConditionNode::Predicate { column, operator, input } => Some(ConditionSpec::Predicate {
+            column: app.column_label(*column),
+            op: *operator,
+            value: input.clone(),
+        }),
+        ConditionNode::Group { children, .. } if children.is_empty() => None,
+        ConditionNode::Group { operator, children } => {
+            let children = children.iter().filter_map(|child| condition_spec(app, child)).collect();
+            Some(match operator {
+                LogicalOperator::And => ConditionSpec::All { all: children },
+                LogicalOperator::Or => ConditionSpec::Any { any: children },
+            })
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::Table;
+
+    const EMPLOYEES: &str = r#"
+        columns = [
+            { name = "EMPLOYEE_ID", type = "NUMBER(6)", primary_key = true },
+            { name = "LAST_NAME", type = "VARCHAR2(25)", nullable = false },
+            { name = "SALARY", type = "NUMBER(8,2)" },
+            "NOTES",
+        ]
+    "#;
+
+    /// Apply a spec to the editor state of the EMPLOYEES table, returning the errors it reports.
+    fn apply(spec: &str, dialect: Dialect) -> (App, Vec<String>) {
+        let spec: QuerySpec = toml::from_str(spec).unwrap();
+        let table: Table = toml::from_str(EMPLOYEES).unwrap();
+        let mut app = App::new(vec![("EMPLOYEES".to_string(), table)], dialect, Vec::new());
+        let errors = spec.apply(&mut app);
+        (app, errors)
+    }
+
+    fn load(spec: &str, dialect: Dialect) -> App {
+        let (app, errors) = apply(spec, dialect);
+        assert!(errors.is_empty(), "{:?}", errors);
+        app
+    }
+
+    #[test]
+    fn generates_the_script_of_a_spec() {
+        let app = load(
+            r#"
+                tables = ["EMPLOYEES"]
+                select = ["LAST_NAME", { column = "SALARY", alias = "PAY" }]
+                order_by = [{ column = "SALARY", order = "desc" }]
+                where = { column = "SALARY", op = ">=", value = "1000" }
+                limit = { rows = 5 }
+            "#,
+            Dialect::Postgres,
+        );
+        assert_eq!(
+            app.generate_query().unwrap(),
+            "SELECT\n\tLAST_NAME,\n\tSALARY AS PAY\nFROM EMPLOYEES\nWHERE\n\tSALARY >= 1000\nORDER BY\n\tPAY DESC\nLIMIT 5;"
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let unknown_field = |spec: &str| toml::from_str::<QuerySpec>(spec).err().map(|err| err.message().to_string());
+        let typo = "tables = [\"EMPLOYEES\"]\nwhere = { column = \"LAST_NAME\", op = \"=\", valeu = \"King\" }";
+        assert!(unknown_field(typo).unwrap().starts_with("unknown field `valeu`"));
+        let typo = "tables = [\"EMPLOYEES\"]\nwhere = { all = [{ colum = \"LAST_NAME\", op = \"is null\" }] }";
+        assert!(unknown_field(typo).unwrap().starts_with("unknown field `colum`"));
+        let typo = "tables = [\"EMPLOYEES\"]\nselect = [{ column = \"LAST_NAME\", alais = \"NAME\" }]";
+        assert!(unknown_field(typo).unwrap().starts_with("unknown field `alais`"));
+        let typo = "tables = [\"EMPLOYEES\"]\ngroup_by = [{ column = \"LAST_NAME\", havign = \"> 1\" }]";
+        assert!(unknown_field(typo).unwrap().starts_with("unknown field `havign`"));
+        let typo = "tables = [\"EMPLOYEES\"]\norder_by = [{ column = \"LAST_NAME\", oder = \"desc\" }]";
+        assert!(unknown_field(typo).unwrap().starts_with("unknown field `oder`"));
+        let misplaced = "tables = [\"EMPLOYEES\"]\nwhere = { column = \"LAST_NAME\", op = \"=\", value = \"King\", limit = 5 }";
+        assert!(toml::from_str::<QuerySpec>(misplaced).is_err());
+        assert!(toml::from_str::<QuerySpec>("tables = [\"EMPLOYEES\"]\nlimit = { row = 5 }").is_err());
+    }
+
+    #[test]
+    fn rejects_empty_where_values() {
+        let spec = |op: &str| format!("tables = [\"EMPLOYEES\"]\nwhere = {{ column = \"LAST_NAME\", op = \"{}\", value = \"\" }}", op);
+        assert_eq!(apply(&spec("raw"), Dialect::Oracle).1.len(), 1);
+        assert_eq!(apply(&spec("="), Dialect::Oracle).1.len(), 1);
+        assert!(apply(&spec("is null"), Dialect::Oracle).1.is_empty());
+    }
+
+    #[test]
+    fn refuses_statements_changing_every_row() {
+        let app = load("tables = [\"EMPLOYEES\"]\nstatement = \"delete\"", Dialect::Oracle);
+        assert!(app.generate_query().is_err());
+        let app = load(
+            "tables = [\"EMPLOYEES\"]\nstatement = \"delete\"\nwhere = { column = \"EMPLOYEE_ID\", op = \"=\", value = \"100\" }",
+            Dialect::Oracle,
+        );
+        assert!(app.generate_query().unwrap().ends_with("DELETE FROM EMPLOYEES\nWHERE\n\tEMPLOYEE_ID = 100\n;"));
+    }
+
+    #[test]
+    fn refuses_keyset_pagination_on_nullable_keys() {
+        let spec = |order_by: &str| format!("tables = [\"EMPLOYEES\"]\norder_by = {}\nlimit = {{ rows = 10, keyset = true }}", order_by);
+        assert!(load(&spec("[\"SALARY\"]"), Dialect::Oracle).generate_query().is_err());
+        assert!(load(&spec("[]"), Dialect::Oracle).generate_query().is_err());
+        let script = load(&spec("[\"LAST_NAME\", \"EMPLOYEE_ID\"]"), Dialect::Postgres).generate_query().unwrap();
+        assert!(script.contains("WHERE\n\tLAST_NAME > :last_last_name OR\n\t(LAST_NAME = :last_last_name AND EMPLOYEE_ID > :last_employee_id)"));
+    }
+
+    #[test]
+    fn pages_after_the_distinct_keys() {
+        let spec = r#"
+            tables = ["EMPLOYEES"]
+            select = ["LAST_NAME", "EMPLOYEE_ID"]
+            distinct_on = ["LAST_NAME"]
+            order_by = [{ column = "EMPLOYEE_ID", order = "desc" }]
+            limit = { rows = 10, keyset = true }
+        "#;
+        let script = load(spec, Dialect::Postgres).generate_query().unwrap();
+        assert!(script.contains("WHERE\n\tLAST_NAME > :last_last_name\nORDER BY"), "{}", script);
+        // Plain DISTINCT cannot sort on what it does not select
+        let spec = "tables = [\"EMPLOYEES\"]\nselect = [\"LAST_NAME\"]\ndistinct = true\n\
+                    order_by = [\"EMPLOYEE_ID\"]\nlimit = { rows = 10, keyset = true }";
+        assert!(load(spec, Dialect::Postgres).generate_query().is_err());
+    }
+
+    #[test]
+    fn warns_about_what_the_query_leaves_out() {
+        let spec = "tables = [\"EMPLOYEES\"]\nselect = [\"LAST_NAME\"]\ndistinct = true\n\
+                    order_by = [{ column = \"LAST_NAME\", nulls = \"last\" }, \"SALARY\"]";
+        assert_eq!(load(spec, Dialect::Postgres).query_warnings().len(), 1);
+        assert_eq!(load(spec, Dialect::Mysql).query_warnings().len(), 2);
+        let spec = "tables = [\"EMPLOYEES\"]\nselect = [\"LAST_NAME\"]\ngroup_by = [\"SALARY\"]";
+        assert_eq!(load(spec, Dialect::Oracle).query_warnings().len(), 1);
+    }
+
+    #[test]
+    fn keeps_a_session_through_a_round_trip() {
+        let spec = r#"
+            tables = ["EMPLOYEES"]
+            expressions = [{ name = "ANNUAL", sql = "SALARY * 12", selected = false }, { name = "TAG", sql = "NOTES" }]
+            limit = { offset = 20, rownum = true }
+        "#;
+        let saved = toml::to_string(&QuerySpec::from_app(&load(spec, Dialect::Oracle))).unwrap();
+        let app = load(&saved, Dialect::Oracle);
+        let selected: Vec<usize> = app.specified_columns.selected_columns.iter().map(|selected| selected.column).collect();
+        assert_eq!(selected, vec![5]);
+        assert!(app.pagination == Pagination { rows: None, offset: 20, rownum: true, keyset: false });
+    }
+
+    #[test]
+    fn keeps_the_order_and_aliases_of_grouped_columns() {
+        let app = load(
+            r#"
+                tables = ["EMPLOYEES"]
+                select = [{ column = "SALARY", alias = "TOTAL" }, { column = "LAST_NAME", alias = "NAME" }]
+                group_by = ["LAST_NAME"]
+                aggregates = [{ function = "sum", column = "SALARY" }, { function = "count", alias = "N" }]
+            "#,
+            Dialect::Postgres,
+        );
+        assert!(app.query_warnings().is_empty());
+        assert_eq!(
+            app.generate_query().unwrap(),
+            "SELECT\n\tSUM(SALARY) AS TOTAL,\n\tLAST_NAME AS NAME,\n\tCOUNT(*) AS N\nFROM EMPLOYEES\nGROUP BY\n\tLAST_NAME;"
+        );
+        let saved = toml::to_string(&QuerySpec::from_app(&app)).unwrap();
+        assert_eq!(load(&saved, Dialect::Postgres).generate_query(), app.generate_query());
+        let taken = "tables = [\"EMPLOYEES\"]\nselect = [{ column = \"SALARY\", alias = \"N\" }]\naggregates = [{ function = \"count\", alias = \"N\" }]";
+        assert_eq!(apply(taken, Dialect::Postgres).1, vec!["aggregates: `COUNT(*)`: the alias N is already used"]);
+    }
+
+    #[test]
+    fn saves_the_files_of_tables_outside_the_tables_directory() {
+        let mut app = load("tables = [\"EMPLOYEES\"]", Dialect::Oracle);
+        app.tables[0].file = Some(PathBuf::from("/defs/EMPLOYEES.toml"));
+        let spec = QuerySpec::from_app(&app);
+        assert!(spec.tables.is_empty());
+        assert_eq!(spec.table_files, vec![PathBuf::from("/defs/EMPLOYEES.toml")]);
+    }
+}
@@ -0,0 +1,274 @@
+use serde::Deserialize;
+use std::{fmt, fs, path::Path};
+
+use crate::{de::StringOrMap, dialect::Dialect};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Table {
+    pub columns: Vec<Column>,
+    /// Dialect used when none is given on the command line.
+    pub dialect: Option<Dialect>,
+    /// Alias used to qualify the columns when the table is joined with others.
+    pub alias: Option<String>,
+    pub comment: Option<String>,
+    /// Indexes created along with the table by the `ddl` command.
+    #[serde(default)]
+    pub indexes: Vec<Index>,
+}
+
+/// An index of a table definition:
+///
+/// ```toml
+/// indexes = [{ columns = ["LAST_NAME", "FIRST_NAME"] }, { name = "EMP_EMAIL_UK", columns = ["EMAIL"], unique = true }]
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Index {
+    /// Named after the table and its columns when left out
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+    #[serde(default)]
+    pub unique: bool,
+}
+
+impl Table {
+    /// Read a table definition file.
+    pub fn load(path: &Path) -> Result<Table, String> {
+        let str = fs::read_to_string(path).map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+        let table: Table = toml::from_str(&str).map_err(|err| format!("invalid table definition {}: {}", path.display(), err))?;
+        for index in &table.indexes {
+            if index.columns.is_empty() {
+                return Err(format!("invalid table definition {}: index without columns", path.display()));
+            }
+            if let Some(unknown) = index.columns.iter().find(|name| !table.columns.iter().any(|column| column.name == **name)) {
+                return Err(format!("invalid table definition {}: index on unknown column `{}`", path.display(), unknown));
+            }
+        }
+        Ok(table)
+    }
+}
+
+/// A column of a table definition.
+///
+/// In the table TOML a column is either a plain name or a table with typed metadata:
+///
+/// ```toml
+/// columns = [
+///     "column1",
+///     { name = "column2", type = "NUMBER(10,2)", nullable = false, primary_key = true },
+///     { name = "column3", references = "OTHER_TABLE.column1" },
+/// ]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "StringOrMap<TypedColumn>")]
+pub struct Column {
+    pub name: String,
+    pub data_type: Option<DataType>,
+    pub nullable: bool,
+    pub primary_key: bool,
+    pub default: Option<String>,
+    pub comment: Option<String>,
+    pub references: Option<ForeignKey>,
+}
+
+/// Column of another table that a column refers to, used to infer join conditions.
+#[derive(Debug, Clone)]
+pub struct ForeignKey {
+    pub table: String,
+    pub column: String,
+}
+
+impl Column {
+    /// Create an untyped, nullable column with only a name.
+    pub fn untyped(name: String) -> Column {
+        Column {
+            name,
+            data_type: None,
+            nullable: true,
+            primary_key: false,
+            default: None,
+            comment: None,
+            references: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TypedColumn {
+    name: String,
+    #[serde(rename = "type")]
+    data_type: Option<String>,
+    #[serde(default = "default_nullable")]
+    nullable: bool,
+    #[serde(default)]
+    primary_key: bool,
+    default: Option<String>,
+    comment: Option<String>,
+    references: Option<String>,
+}
+
+fn default_nullable() -> bool {
+    true
+}
+
+impl TryFrom<StringOrMap<TypedColumn>> for Column {
+    type Error = String;
+
+    fn try_from(definition: StringOrMap<TypedColumn>) -> Result<Self, Self::Error> {
+        match definition {
+            StringOrMap::String(name) => Ok(Column::untyped(name)),
+            StringOrMap::Map(TypedColumn { name, data_type, nullable, primary_key, default, comment, references }) => {
+                let data_type = data_type
+                    .map(DataType::try_from)
+                    .transpose()
+                    .map_err(|err| format!("column `{name}`: {err}"))?;
+                let references = match references {
+                    Some(reference) => match reference.split_once('.') {
+                        Some((table, column)) => Some(ForeignKey {
+                            table: table.trim().to_string(),
+                            column: column.trim().to_string(),
+                        }),
+                        None => return Err(format!("column `{name}`: reference `{reference}` is not of the form TABLE.COLUMN")),
+                    },
+                    None => None,
+                };
+                Ok(Column {
+                    name,
+                    data_type,
+                    nullable,
+                    primary_key,
+                    default,
+                    comment,
+                    references,
+                })
+            },
+        }
+    }
+}
+
+/// SQL data type of a column, written in Oracle notation in the table TOML (e.g. `VARCHAR2(100)`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataType {
+    Number { precision: Option<u32>, scale: Option<u32> },
+    Integer,
+    Float,
+    Char(Option<u32>),
+    Varchar2(Option<u32>),
+    Date,
+    Timestamp,
+    Clob,
+    Blob,
+}
+
+impl DataType {
+    pub fn is_numeric(self) -> bool {
+        matches!(self, DataType::Number { .. } | DataType::Integer | DataType::Float)
+    }
+}
+
+impl TryFrom<String> for DataType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.trim().to_uppercase();
+        let (name, args) = match value.split_once('(') {
+            Some((name, rest)) => {
+                let Some(args) = rest.strip_suffix(')') else {
+                    return Err(format!("missing closing parenthesis in type `{value}`"));
+                };
+                let args = args
+                    .split(',')
+                    .map(|arg| arg.trim().parse::<u32>())
+                    .collect::<Result<Vec<u32>, _>>()
+                    .map_err(|_| format!("invalid size in type `{value}`"))?;
+                (name.trim(), args)
+            },
+            None => (value.as_str(), Vec::new()),
+        };
+
+        let data_type = match (name, args.as_slice()) {
+            ("NUMBER" | "NUMERIC" | "DECIMAL", []) => DataType::Number { precision: None, scale: None },
+            ("NUMBER" | "NUMERIC" | "DECIMAL", [precision]) => DataType::Number { precision: Some(*precision), scale: None },
+            ("NUMBER" | "NUMERIC" | "DECIMAL", [precision, scale]) => DataType::Number { precision: Some(*precision), scale: Some(*scale) },
+            ("INTEGER" | "INT", []) => DataType::Integer,
+            ("FLOAT" | "BINARY_DOUBLE", []) => DataType::Float,
+            ("CHAR", []) => DataType::Char(None),
+            ("CHAR", [size]) => DataType::Char(Some(*size)),
+            ("VARCHAR2" | "VARCHAR", []) => DataType::Varchar2(None),
+            ("VARCHAR2" | "VARCHAR", [size]) => DataType::Varchar2(Some(*size)),
+            ("DATE", []) => DataType::Date,
+            ("TIMESTAMP", []) => DataType::Timestamp,
+            ("CLOB" | "TEXT", []) => DataType::Clob,
+            ("BLOB", []) => DataType::Blob,
+            _ => return Err(format!("unsupported column type `{value}`")),
+        };
+        Ok(data_type)
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataType::Number { precision: None, .. } => write!(f, "NUMBER"),
+            DataType::Number { precision: Some(precision), scale: None } => write!(f, "NUMBER({precision})"),
+            DataType::Number { precision: Some(precision), scale: Some(scale) } => write!(f, "NUMBER({precision},{scale})"),
+            DataType::Integer => write!(f, "INTEGER"),
+            DataType::Float => write!(f, "FLOAT"),
+            DataType::Char(None) => write!(f, "CHAR"),
+            DataType::Char(Some(size)) => write!(f, "CHAR({size})"),
+            DataType::Varchar2(None) => write!(f, "VARCHAR2"),
+            DataType::Varchar2(Some(size)) => write!(f, "VARCHAR2({size})"),
+            DataType::Date => write!(f, "DATE"),
+            DataType::Timestamp => write!(f, "TIMESTAMP"),
+            DataType::Clob => write!(f, "CLOB"),
+            DataType::Blob => write!(f, "BLOB"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(toml: &str) -> Result<Vec<Column>, toml::de::Error> {
+        toml::from_str::<Table>(toml).map(|table| table.columns)
+    }
+
+    #[test]
+    fn reads_plain_and_typed_columns() {
+        let columns = columns(
+            r#"columns = ["NOTES", { name = "ID", type = "number(6)", nullable = false, primary_key = true, references = "OTHER.ID" }]"#,
+        )
+        .unwrap();
+        assert_eq!(columns[0].name, "NOTES");
+        assert_eq!(columns[0].data_type, None);
+        assert!(columns[0].nullable);
+        assert_eq!(columns[1].data_type, Some(DataType::Number { precision: Some(6), scale: None }));
+        assert!(!columns[1].nullable && columns[1].primary_key);
+        let reference = columns[1].references.as_ref().unwrap();
+        assert_eq!((reference.table.as_str(), reference.column.as_str()), ("OTHER", "ID"));
+    }
+
+    #[test]
+    fn rejects_unknown_column_fields() {
+        assert!(columns(r#"columns = [{ name = "ID", type = "NUMBER", primary = true }]"#).is_err());
+        assert!(columns(r#"columns = [{ name = "ID", nulable = false }]"#).is_err());
+        let err = columns(r#"columns = [{ name = "ID", typ = "NUMBER" }]"#).unwrap_err();
+        assert!(err.message().starts_with("unknown field `typ`, expected one of"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_unknown_table_and_index_fields() {
+        assert!(columns("columns = [\"ID\"]\nindexes = [{ columns = [\"ID\"], uniqe = true }]").is_err());
+        assert!(columns("columns = [\"ID\"]\nindex = [{ columns = [\"ID\"] }]").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_types_and_references() {
+        assert!(columns(r#"columns = [{ name = "ID", type = "NUMBER(6" }]"#).is_err());
+        assert!(columns(r#"columns = [{ name = "ID", type = "GEOMETRY" }]"#).is_err());
+        assert!(columns(r#"columns = [{ name = "ID", references = "OTHER" }]"#).is_err());
+    }
+}
@@ -4,6 +4,7 @@ use ratatui::{
         Constraint,
         Direction,
         Layout,
+        Margin,
         Rect
     },
     style::{
@@ -20,24 +21,32 @@ use ratatui::{
     },
     widgets::{
         Block,
+        Clear,
         List,
         ListItem,
         Paragraph,
         Padding,
+        Scrollbar,
+        ScrollbarOrientation,
+        ScrollbarState,
+        StatefulWidget,
         Tabs,
         Widget,
         Borders,
+        Wrap,
     },
     Frame
 };
 
-use crate::app::{
-    App, CurrentTab, CurrentlyEditing, OrderdFlag, SelectedFlag
+use crate::{
+    app::{App, ConditionNode, CurrentTab, CurrentlyEditing, LIMIT_ROWS},
+    functions,
+    query::{Expr, NullsOrder, Operator, SortOrder},
 };
 
 use strum::IntoEnumIterator;
 
-pub fn ui(frame: &mut Frame, app: &App) {
+pub fn ui(frame: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -77,15 +86,33 @@ pub fn ui(frame: &mut Frame, app: &App) {
     
     frame.render_widget(title, header_chunks[1]);
     
-    let footer = Line::raw("◄ ► to change tab | Press q to quit")
+    let footer = Line::raw("◄ ► to change tab | m: statement | p: preview | y: copy SQL | s: save session | Press q to quit")
         .centered();
     
+    // Render the SQL preview next to the main panel
+    let mut main_area = chunks[1];
+    if app.show_preview {
+        let main_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(55),
+                Constraint::Percentage(45),
+            ])
+            .split(chunks[1]);
+        main_area = main_chunks[0];
+        render_preview(app, main_chunks[1], frame.buffer_mut());
+    }
+
     // Render main panel depeding on the current tab
     match app.current_tab {
-        CurrentTab::Init => app.current_tab.render_init(app, chunks[1], frame.buffer_mut()),
-        CurrentTab::Select => app.current_tab.render_select(app, chunks[1], frame.buffer_mut()),
-        CurrentTab::OrderBy => app.current_tab.render_order(app, chunks[1], frame.buffer_mut()),
-        CurrentTab::Where => app.current_tab.render_where(app, chunks[1], frame.buffer_mut()),
+        CurrentTab::Init => app.current_tab.render_init(app, main_area, frame.buffer_mut()),
+        CurrentTab::From => app.current_tab.render_from(app, main_area, frame.buffer_mut()),
+        CurrentTab::Select => app.current_tab.render_select(app, main_area, frame.buffer_mut()),
+        CurrentTab::OrderBy => app.current_tab.render_order(app, main_area, frame.buffer_mut()),
+        CurrentTab::Where => app.current_tab.render_where(app, main_area, frame.buffer_mut()),
+        CurrentTab::GroupBy => app.current_tab.render_group(app, main_area, frame.buffer_mut()),
+        CurrentTab::Limit => app.current_tab.render_limit(app, main_area, frame.buffer_mut()),
+        CurrentTab::Values => app.current_tab.render_values(app, main_area, frame.buffer_mut()),
     }
     
 
@@ -93,7 +120,42 @@ pub fn ui(frame: &mut Frame, app: &App) {
     
     frame.render_widget(footer, chunks[2]);
     
-    if let Some(editing) = &app.currently_editing {
+    if let Some(CurrentlyEditing::JoinCondition) = &app.currently_editing {
+        let table = &app.tables[app.current_column];
+        let title = format!("Enter the join condition for {} {}", table.name, table.alias);
+        render_input_popup(frame, title, "ON", &app.constraint_input, &None);
+    } else if let Some(CurrentlyEditing::Having) = &app.currently_editing {
+        let title = format!("Enter a HAVING condition for {}", app.group_label(app.current_column));
+        render_input_popup(frame, title, "HAVING", &app.constraint_input, &None);
+    } else if let Some(CurrentlyEditing::InitKey) = &app.currently_editing {
+        let title = "Enter the name of the setting to add".to_string();
+        render_input_popup(frame, title, "Key (⇥ known settings)", &app.constraint_input, &app.input_error);
+    } else if let Some(CurrentlyEditing::InitValue) = &app.currently_editing {
+        let title = format!("Enter the value of {}", app.init_config[app.current_column].key);
+        render_input_popup(frame, title, "Value (⇥ valid values)", &app.constraint_input, &app.input_error);
+    } else if let Some(CurrentlyEditing::Alias) = &app.currently_editing {
+        let title = format!("Enter an alias for {}", app.column_label(app.current_column));
+        render_input_popup(frame, title, "AS", &app.constraint_input, &app.input_error);
+    } else if let Some(CurrentlyEditing::AggregateAlias) = &app.currently_editing {
+        let title = format!("Enter an alias for {}", app.group_label(app.current_column));
+        render_input_popup(frame, title, "AS", &app.constraint_input, &app.input_error);
+    } else if let Some(CurrentlyEditing::Expression | CurrentlyEditing::NewExpression) = &app.currently_editing {
+        let title = "Enter the name and SQL of the expression column".to_string();
+        let input_title = match app.current_function() {
+            Some((i, function)) => format!("NAME = SQL (⇥ functions: {} {}/{})", function.name, i + 1, functions::catalogue(app.dialect).len()),
+            None => "NAME = SQL (⇥ functions)".to_string(),
+        };
+        render_input_popup(frame, title, &input_title, &app.constraint_input, &app.input_error);
+    } else if let Some(CurrentlyEditing::LimitValue) = &app.currently_editing {
+        let title = format!("Enter the {} (empty for none)", LIMIT_ROWS[app.current_column].to_lowercase());
+        render_input_popup(frame, title, LIMIT_ROWS[app.current_column], &app.constraint_input, &app.input_error);
+    } else if let Some(CurrentlyEditing::Value) = &app.currently_editing {
+        let title = format!("Enter the value of {}: a literal, NULL or =SQL (empty for none)", app.column_label(app.current_column));
+        render_input_popup(frame, title, "Value", &app.constraint_input, &app.input_error);
+    } else if let Some(CurrentlyEditing::SessionName) = &app.currently_editing {
+        let title = "Save the session to reopen it with `edit --session`".to_string();
+        render_input_popup(frame, title, "Name", &app.constraint_input, &app.input_error);
+    } else if let Some(CurrentlyEditing::Constraint | CurrentlyEditing::NewConstraint) = &app.currently_editing {
         let popup_block = Block::default()
         .title("Enter a constraint for the selected column")
         .borders(Borders::NONE)
@@ -105,17 +167,57 @@ pub fn ui(frame: &mut Frame, app: &App) {
         let popup_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .margin(1)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .constraints([Constraint::Percentage(40), Constraint::Length(15), Constraint::Min(0)])
             .split(area);
         
-        let column_block = Block::default().title("Column").borders(Borders::ALL);
-        let constraint_block = Block::default().title("Constraint").borders(Borders::ALL);
+        let column_block = Block::default().title("Column (↑ ↓)").borders(Borders::ALL);
+        let operator_block = Block::default().title("Operator (⇥)").borders(Borders::ALL);
+        let values_title = match app.editing_operator {
+            Operator::Raw => "SQL",
+            Operator::In | Operator::Between => "Values (a, b)",
+            _ => "Value",
+        };
+        let constraint_block = Block::default().title(values_title).borders(Borders::ALL);
         
-        let column_text = Paragraph::new(app.base_columns[app.current_column].clone()).block(column_block);
+        let column = &app.base_columns[app.editing_column];
+        let mut column_lines = vec![Line::from(app.column_label(app.editing_column))];
+        let mut attributes = Vec::new();
+        match column.data_type {
+            Some(data_type) => attributes.push(data_type.to_string()),
+            None => attributes.push("untyped, values are text (SQL for numbers)".to_string()),
+        }
+        if column.primary_key {
+            attributes.push("PRIMARY KEY".to_string());
+        } else if !column.nullable {
+            attributes.push("NOT NULL".to_string());
+        }
+        if let Some(default) = &column.default {
+            attributes.push(format!("DEFAULT {default}"));
+        }
+        if !attributes.is_empty() {
+            column_lines.push(Line::from(Span::styled(attributes.join(" "), Style::default().fg(Color::Gray))));
+        }
+        if let Some(comment) = &column.comment {
+            column_lines.push(Line::from(Span::styled(comment.clone(), Style::default().fg(Color::Gray))));
+        }
+        let column_text = Paragraph::new(column_lines).block(column_block);
         frame.render_widget(column_text, popup_chunks[0]);
         
-        let constraint_text = Paragraph::new(app.constraint_input.clone()).block(constraint_block);
-        frame.render_widget(constraint_text, popup_chunks[1]);
+        let operator_text = Paragraph::new(app.editing_operator.to_string()).block(operator_block);
+        frame.render_widget(operator_text, popup_chunks[1]);
+
+        let mut constraint_lines = vec![Line::from(app.constraint_input.clone())];
+        if let Some(err) = &app.input_error {
+            constraint_lines.push(Line::from(Span::styled(err.clone(), Style::default().fg(Color::LightRed))));
+        }
+        let constraint_text = Paragraph::new(constraint_lines)
+            .wrap(Wrap { trim: false })
+            .block(constraint_block);
+        frame.render_widget(constraint_text, popup_chunks[2]);
+    }
+
+    if let Some(notice) = &app.notice {
+        render_notice_popup(frame, notice);
     }
 }
 
@@ -127,133 +229,277 @@ impl CurrentTab {
             .into()
     }
     
-    fn render_init(self, app: &App, area: Rect, buf: &mut Buffer) {
+    fn render_init(self, app: &mut App, area: Rect, buf: &mut Buffer) {
         let mut list_items = Vec::<ListItem>::new();
-        for i in 0..app.init_config.len() {
+        let rows = app.visible_rows();
+        for &i in &rows {
             let mut text_color = Color::DarkGray;
             if i == app.current_column {
                 text_color = Color::White;
             }
             
+            let setting = &app.init_config[i];
+            let marker = if setting.enabled { "✓" } else { " " };
             list_items.push(ListItem::new(Line::from(Span::styled(
-                format!("{} {}", app.init_config[i].0, app.init_config[i].1),
+                format!("{} {}", marker, app.dialect.render_setting(setting)),
                 Style::default().fg(text_color),
             ))));
         }
 
-        List::new(list_items)
-            .block(self.block())
-            .render(area, buf);
+        let block = self.block_for(app).title(" ⏎: on/off | e: edit | t: kind | a: add | d: delete ");
+        self.render_rows(app, list_items, &rows, block, area, buf);
     }
 
-    fn render_select(self, app: &App, area: Rect, buf: &mut Buffer) {
+    fn render_from(self, app: &mut App, area: Rect, buf: &mut Buffer) {
         let mut list_items = Vec::<ListItem>::new();
-        let mut starting_point = 0;
-        if app.current_column as isize - 4 >= 0 {
-            starting_point = app.current_column - 4;
+        let rows = app.visible_rows();
+        for &i in &rows {
+            let table = &app.tables[i];
+            let mut text_color = Color::DarkGray;
+            if i == app.current_column {
+                text_color = Color::White;
+            }
+
+            let text = if i == 0 {
+                format!("{} {}", table.name, table.alias)
+            } else if table.join_condition.is_empty() {
+                format!("CROSS JOIN {} {}", table.name, table.alias)
+            } else {
+                format!("{} {} {} ON {}", table.join_kind.keyword(), table.name, table.alias, table.join_condition)
+            };
+            list_items.push(ListItem::new(Line::from(Span::styled(
+                text,
+                Style::default().fg(text_color),
+            ))));
         }
-        for i in starting_point..app.base_columns.len() {
+
+        let block = self.block_for(app);
+        self.render_rows(app, list_items, &rows, block, area, buf);
+    }
+
+    fn render_select(self, app: &mut App, area: Rect, buf: &mut Buffer) {
+        let mut list_items = Vec::<ListItem>::new();
+        let rows = app.visible_rows();
+        for &i in &rows {
             let mut text_color = Color::DarkGray;
             if i == app.current_column {
                 text_color = Color::White;
             }
 
-            match app.specified_columns.selected_columns[i] {
-                SelectedFlag::Selected => {
-                    list_items.push(ListItem::new(Line::from(Span::styled(
-                        format!("✓ {}", app.base_columns[i]),
-                        Style::default().fg(text_color),
-                    ))));
+            let mut label = app.column_label(i);
+            if let Some(sql) = app.expression(i) {
+                label = format!("ƒ {} = {}", label, sql);
+            }
+            let text = match app.selected_column(i) {
+                Some((position, selected)) => {
+                    let key = if selected.distinct_on { " ◆" } else { "" };
+                    match &selected.alias {
+                        Some(alias) => format!("✓ {} {} AS {}{}", position + 1, label, alias, key),
+                        None => format!("✓ {} {}{}", position + 1, label, key),
+                    }
                 },
-                SelectedFlag::NotSelected => {
-                    list_items.push(ListItem::new(Line::from(Span::styled(
-                        format!("{}", app.base_columns[i]),
-                        Style::default().fg(text_color),
-                    ))));
-                }
+                None => label,
+            };
+            list_items.push(ListItem::new(Line::from(Span::styled(
+                text,
+                Style::default().fg(text_color),
+            ))));
+        }
+        
+        let mut block = self.block_for(app).title(" ⏎: select | a: all | e: alias | K J: move | f: add expression | d: delete expression | u: distinct | o: distinct on | *: star ");
+        if app.distinct {
+            let distinct = if app.distinct_on_columns().is_empty() { " DISTINCT " } else { " DISTINCT ON ◆ " };
+            block = block.title(Line::from(distinct).right_aligned());
+        }
+        block = block.title_bottom(Line::from(format!(" *: {} ", app.select_star)).right_aligned());
+        self.render_rows(app, list_items, &rows, block, area, buf);
+    }
+    
+    fn render_order(self, app: &mut App, area: Rect, buf: &mut Buffer) {
+        let mut list_items = Vec::<ListItem>::new();
+        let rows = app.visible_rows();
+        for &i in &rows {
+            let mut text_color = Color::DarkGray;
+            if i == app.current_column {
+                text_color = Color::White;
             }
 
+            let text = match app.order_key(i) {
+                Some((priority, key)) => {
+                    let arrow = match key.order {
+                        SortOrder::Asc => "↑",
+                        SortOrder::Desc => "↓",
+                    };
+                    let nulls = match key.nulls {
+                        Some(NullsOrder::First) => " NULLS FIRST",
+                        Some(NullsOrder::Last) => " NULLS LAST",
+                        None => "",
+                    };
+                    format!("{} {} {}{}", priority + 1, arrow, app.column_label(i), nulls)
+                },
+                None => app.column_label(i),
+            };
+            list_items.push(ListItem::new(Line::from(Span::styled(
+                text,
+                Style::default().fg(text_color),
+            ))));
         }
         
-        List::new(list_items)
-            .block(self.block())
-            .render(area, buf);
+        let block = self.block_for(app).title(" ⏎: asc/desc/off | n: nulls | K J: priority ");
+        self.render_rows(app, list_items, &rows, block, area, buf);
     }
     
-    fn render_order(self, app: &App, area: Rect, buf: &mut Buffer) {
+    fn render_where(self, app: &mut App, area: Rect, buf: &mut Buffer) {
         let mut list_items = Vec::<ListItem>::new();
-        let mut starting_point = 0;
-        if app.current_column as isize - 4 >= 0 {
-            starting_point = app.current_column - 4;
-        }
-        for i in starting_point..app.base_columns.len() {
+        let where_clause = &app.specified_columns.where_clause;
+        let tree_rows = where_clause.rows();
+        let rows = app.visible_rows();
+        for &i in &rows {
+            let (depth, path) = &tree_rows[i];
             let mut text_color = Color::DarkGray;
             if i == app.current_column {
                 text_color = Color::White;
             }
 
-            match app.specified_columns.ordered_columns[i] {
-                OrderdFlag::Asc => {
-                    list_items.push(ListItem::new(Line::from(Span::styled(
-                        format!("↑ {}", app.base_columns[i]),
-                        Style::default().fg(text_color),
-                    ))));
+            let text = match where_clause.get(path) {
+                ConditionNode::Predicate { column, operator, input } => {
+                    match app.where_predicate(*column, *operator, input) {
+                        Ok(predicate) => format!("{} {}", app.column_label(*column), predicate.constraint.render(app.dialect)),
+                        Err(_) => format!("{} {} {} (invalid)", app.column_label(*column), operator, input),
+                    }
                 },
-                OrderdFlag::Desc => {
-                    list_items.push(ListItem::new(Line::from(Span::styled(
-                        format!("↓ {}", app.base_columns[i]),
-                        Style::default().fg(text_color),
-                    ))));
+                ConditionNode::Group { operator, children } if children.is_empty() => {
+                    format!("{} (empty)", operator.keyword())
                 },
-                OrderdFlag::Off => {
-                    list_items.push(ListItem::new(Line::from(Span::styled(
-                        format!("{}", app.base_columns[i]),
-                        Style::default().fg(text_color),
-                    ))));
-                }
-            }
-
+                ConditionNode::Group { operator, .. } => operator.keyword().to_string(),
+            };
+            list_items.push(ListItem::new(Line::from(Span::styled(
+                format!("{}{}", "  ".repeat(*depth), text),
+                Style::default().fg(text_color),
+            ))));
         }
-        
-        List::new(list_items)
-            .block(self.block())
-            .render(area, buf);
+
+        let block = self.block_for(app).title(" a: add | g: add group | e: edit | d: delete | ⏎: AND/OR ");
+        self.render_rows(app, list_items, &rows, block, area, buf);
     }
     
-    fn render_where(self, app: &App, area: Rect, buf: &mut Buffer) {
+    fn render_group(self, app: &mut App, area: Rect, buf: &mut Buffer) {
         let mut list_items = Vec::<ListItem>::new();
-        let mut starting_point = 0;
-        if app.current_column as isize - 4 >= 0 {
-            starting_point = app.current_column - 4;
+        let rows = app.visible_rows();
+        // The last row stands for COUNT(*)
+        for &i in &rows {
+            let mut text_color = Color::DarkGray;
+            if i == app.current_column {
+                text_color = Color::White;
+            }
+
+            let mut text = match app.group_expr(i) {
+                Some(Expr::Aggregate { .. } | Expr::CountAll) => format!("Σ {}", app.group_label(i)),
+                Some(_) => format!("# {}", app.group_label(i)),
+                None => app.group_label(i),
+            };
+            if let Some(alias) = app.aggregate_alias(i) {
+                text = format!("{} AS {}", text, alias);
+            }
+            if let Some(having) = app.having_constraint(i) {
+                text = format!("{} HAVING {}", text, having);
+            }
+            list_items.push(ListItem::new(Line::from(Span::styled(
+                text,
+                Style::default().fg(text_color),
+            ))));
         }
-        for i in starting_point..app.base_columns.len() {
+
+        let block = self.block_for(app);
+        self.render_rows(app, list_items, &rows, block, area, buf);
+    }
+
+    fn render_limit(self, app: &mut App, area: Rect, buf: &mut Buffer) {
+        let mut list_items = Vec::<ListItem>::new();
+        let pagination = app.pagination;
+        let rows = app.visible_rows();
+        for &i in &rows {
             let mut text_color = Color::DarkGray;
             if i == app.current_column {
                 text_color = Color::White;
             }
 
-            match &app.specified_columns.where_constraints[i] {
-                Some(constraint) => {
-                    list_items.push(ListItem::new(Line::from(Span::styled(
-                        format!("{} {}", app.base_columns[i], constraint),
-                        Style::default().fg(text_color),
-                    ))));
+            let value = match i {
+                0 => pagination.rows.map_or("all".to_string(), |rows| rows.to_string()),
+                1 if pagination.keyset => format!("{} (not used with keyset pagination)", pagination.offset),
+                1 => pagination.offset.to_string(),
+                2 if pagination.rownum => "ROWNUM (before 12c)".to_string(),
+                2 => "FETCH FIRST".to_string(),
+                _ if !pagination.keyset => "off".to_string(),
+                _ if app.keyset_keys().is_empty() => "on, sort on columns in ORDER BY".to_string(),
+                _ => {
+                    let parameters: Vec<String> = app
+                        .keyset_keys()
+                        .iter()
+                        .map(|key| app.dialect.placeholder(&app.keyset_parameter(key.column)))
+                        .collect();
+                    format!("on, after {}", parameters.join(", "))
                 },
-                None => {
-                    list_items.push(ListItem::new(Line::from(Span::styled(
-                        format!("{}", app.base_columns[i]),
-                        Style::default().fg(text_color),
-                    ))));
-                }
+            };
+            list_items.push(ListItem::new(Line::from(Span::styled(
+                format!("{:<8}{}", LIMIT_ROWS[i], value),
+                Style::default().fg(text_color),
+            ))));
+        }
+
+        let block = self.block_for(app).title(" e: edit | ⏎: toggle ");
+        self.render_rows(app, list_items, &rows, block, area, buf);
+    }
+
+    fn render_values(self, app: &mut App, area: Rect, buf: &mut Buffer) {
+        let mut list_items = Vec::<ListItem>::new();
+        let rows = app.visible_rows();
+        for &i in &rows {
+            let mut text_color = Color::DarkGray;
+            if i == app.current_column {
+                text_color = Color::White;
             }
 
+            let mut text = app.column_label(i);
+            if app.base_columns[i].primary_key {
+                text.push_str(" (key)");
+            }
+            match &app.specified_columns.values[i] {
+                Some(value) => text.push_str(&format!(" = {}", value)),
+                None if app.value_required(i) => text.push_str(" = ? (required by INSERT)"),
+                None => {},
+            }
+            list_items.push(ListItem::new(Line::from(Span::styled(
+                text,
+                Style::default().fg(text_color),
+            ))));
         }
-        
-        List::new(list_items)
-            .block(self.block())
-            .render(area, buf);
+
+        let block = self.block_for(app)
+            .title(" ⏎ e: edit | d: clear ")
+            .title(Line::from(format!(" {} ", app.statement)).right_aligned());
+        self.render_rows(app, list_items, &rows, block, area, buf);
     }
-    
+
+    /// Render the rows of a tab in a viewport that keeps the current row in sight,
+    /// with a scrollbar when they do not all fit.
+    fn render_rows(self, app: &mut App, list_items: Vec<ListItem<'static>>, rows: &[usize], block: Block<'static>, area: Rect, buf: &mut Buffer) {
+        let selected = rows.iter().position(|&i| i == app.current_column);
+        *app.list_state.selected_mut() = selected;
+        app.page_height = block.inner(area).height.max(1) as usize;
+        StatefulWidget::render(List::new(list_items).block(block), area, buf, &mut app.list_state);
+
+        if rows.len() > app.page_height {
+            let mut scrollbar_state = ScrollbarState::new(rows.len().saturating_sub(app.page_height))
+                .position(app.list_state.offset());
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None)
+                .thumb_style(self.palette().c400)
+                .render(area.inner(Margin { vertical: 1, horizontal: 0 }), buf, &mut scrollbar_state);
+        }
+    }
+
     fn block(self) -> Block<'static> {
         Block::bordered()
             .border_set(symbols::border::PROPORTIONAL_TALL)
@@ -261,16 +507,163 @@ impl CurrentTab {
             .border_style(self.palette().c700)
     }
     
+    /// The block of the tab, showing the search filter at the bottom while there is one.
+    fn block_for(self, app: &App) -> Block<'static> {
+        let searching = matches!(app.currently_editing, Some(CurrentlyEditing::Search));
+        if !searching && app.filter.is_empty() {
+            return self.block();
+        }
+        let cursor = if searching { "▏" } else { "" };
+        self.block().title_bottom(format!(" /{}{} ({} shown) ", app.filter, cursor, app.visible_rows().len()))
+    }
+
     const fn palette(self) -> tailwind::Palette {
         match self {
             Self::Init => tailwind::ORANGE,
+            Self::From => tailwind::SKY,
             Self::Select => tailwind::BLUE,
             Self::OrderBy => tailwind::EMERALD,
             Self::Where => tailwind::PURPLE,
+            Self::GroupBy => tailwind::ROSE,
+            Self::Limit => tailwind::TEAL,
+            Self::Values => tailwind::AMBER,
         }
     }
 }
 
+/// Render the script that would be written on quitting, highlighted.
+fn render_preview(app: &mut App, area: Rect, buf: &mut Buffer) {
+    let block = Block::bordered()
+        .title(format!(" SQL: {} ", app.statement))
+        .title_bottom(Line::from(" [ ]: scroll ").right_aligned())
+        .border_set(symbols::border::PROPORTIONAL_TALL)
+        .padding(Padding::horizontal(1))
+        .border_style(tailwind::SLATE.c700);
+    let mut script: String = app.query_warnings().iter().map(|warning| format!("-- warning: {}\n", warning)).collect();
+    script.push_str(&app.generate_query().unwrap_or_else(|err| format!("-- {}", err)));
+
+    // Long lines wrap, so count the lines as wrapped to stop scrolling at the last one
+    let width = usize::from(block.inner(area).width.max(1));
+    let lines: usize = script.lines().map(|line| line.replace('\t', "    ").chars().count().max(1).div_ceil(width)).sum();
+    app.preview_scroll = app.preview_scroll.min(u16::try_from(lines.saturating_sub(1)).unwrap_or(u16::MAX));
+    Paragraph::new(highlight_sql(&script))
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((app.preview_scroll, 0))
+        .render(area, buf);
+}
+
+/// Words highlighted as keywords in the preview.
+const KEYWORDS: &[&str] = &[
+    "ALTER", "AND", "AS", "ASC", "AVG", "BETWEEN", "BY", "COLUMN", "CONFLICT", "COUNT", "CROSS", "DATE",
+    "DELETE", "DESC", "DISTINCT", "DO", "DUPLICATE", "FORMAT", "FROM", "GROUP", "HAVING", "IN", "INNER",
+    "INSERT", "INTO", "IS", "JOIN", "KEY", "LEFT", "LIKE", "MATCHED", "MAX", "MERGE", "MIN", "NOT", "NOTHING",
+    "NULL", "ON", "OR", "ORDER", "SELECT", "SESSION", "SET", "SUM", "THEN", "TIMESTAMP", "UPDATE", "USING",
+    "VALUES", "WHEN", "WHERE",
+];
+
+/// Split SQL into styled spans: keywords, string literals, numbers and comments.
+fn highlight_sql(sql: &str) -> Text<'static> {
+    let keyword_style = Style::default().fg(tailwind::SKY.c400).bold();
+    let string_style = Style::default().fg(tailwind::EMERALD.c400);
+    let number_style = Style::default().fg(tailwind::AMBER.c400);
+    let comment_style = Style::default().fg(Color::DarkGray).italic();
+
+    let lines: Vec<Line> = sql
+        .lines()
+        .map(|line| {
+            // ratatui gives tabs no width
+            let chars: Vec<char> = line.replace('\t', "    ").chars().collect();
+            let mut spans = Vec::<Span>::new();
+            let mut i = 0;
+            while i < chars.len() {
+                let start = i;
+                let c = chars[i];
+                let style = if c == '-' && chars.get(i + 1) == Some(&'-') {
+                    i = chars.len();
+                    comment_style
+                } else if c == '\'' {
+                    i += 1;
+                    while i < chars.len() {
+                        i += 1;
+                        if chars[i - 1] == '\'' {
+                            // a doubled quote is an escaped quote
+                            if chars.get(i) != Some(&'\'') {
+                                break;
+                            }
+                            i += 1;
+                        }
+                    }
+                    string_style
+                } else if c.is_ascii_digit() {
+                    while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                        i += 1;
+                    }
+                    number_style
+                } else if c.is_alphabetic() || c == '_' {
+                    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                        i += 1;
+                    }
+                    let word: String = chars[start..i].iter().collect();
+                    if KEYWORDS.contains(&word.to_ascii_uppercase().as_str()) {
+                        keyword_style
+                    } else {
+                        Style::default()
+                    }
+                } else {
+                    i += 1;
+                    Style::default()
+                };
+                spans.push(Span::styled(chars[start..i].iter().collect::<String>(), style));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    Text::from(lines)
+}
+
+/// Render a popup with a message, dismissed by any key
+fn render_notice_popup(frame: &mut Frame, notice: &str) {
+    let area = centered_rect(60, 25, frame.area());
+    let notice_block = Block::default()
+        .title("Press any key")
+        .borders(Borders::ALL)
+        .padding(Padding::horizontal(1))
+        .style(Style::default().bg(Color::DarkGray));
+    let notice_text = Paragraph::new(notice.to_string())
+        .wrap(Wrap { trim: false })
+        .block(notice_block);
+    frame.render_widget(Clear, area);
+    frame.render_widget(notice_text, area);
+}
+
+/// Render a popup with a single input field
+fn render_input_popup(frame: &mut Frame, title: String, input_title: &str, input: &str, error: &Option<String>) {
+    let popup_block = Block::default()
+        .title(title)
+        .borders(Borders::NONE)
+        .style(Style::default().bg(Color::DarkGray));
+
+    let area = centered_rect(60, 25, frame.area());
+    frame.render_widget(popup_block, area);
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .margin(1)
+        .constraints([Constraint::Percentage(100)])
+        .split(area);
+
+    let input_block = Block::default().title(input_title.to_string()).borders(Borders::ALL);
+    let mut input_lines = vec![Line::from(input.to_string())];
+    if let Some(err) = error {
+        input_lines.push(Line::from(Span::styled(err.clone(), Style::default().fg(Color::LightRed))));
+    }
+    let input_text = Paragraph::new(input_lines)
+        .wrap(Wrap { trim: false })
+        .block(input_block);
+    frame.render_widget(input_text, popup_chunks[0]);
+}
+
 /// helper function to create a centered rect using up certain percentage of the available rect `r`
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     // Cut the given rectangle into three vertical pieces